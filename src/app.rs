@@ -1,15 +1,32 @@
 use crate::file_helper::PathHelper;
 use crate::utils::{
-    buffer_storage::{BufferStorage, FileEntry, FileType, State},
-    cursor::Cursor,
+    buffer_storage::{BufferStorage, DirBuffer, FileEntry, FileType, State},
+    change_recorder::ChangeRecorder,
+    command_line::CommandLine,
+    command_registry::CommandRegistry,
+    commands,
+    cursor::{Cursor, DirPosition},
+    dual_pane::DualPane,
+    finder::Finder,
+    frecency::Frecency,
+    global,
     input_buffer::InputBuffer,
-    system,
-    undo_stack::UndoStack,
+    jump_list::JumpList,
+    macros::Macros,
+    marks::{Mark, Marks},
+    options::Options,
+    popup::Popup,
+    search::{self, Search},
+    shell, substitute, system,
+    tabs::{Tab, Tabs},
+    tree,
+    undo_tree::{Snapshot, UndoTree},
     yank_buffer::YankBuffer,
 };
 use crossterm::cursor::SetCursorStyle;
 use ratatui::widgets::Paragraph;
-use std::{error, fmt, result::Result};
+use regex::Regex;
+use std::{collections::HashMap, error, fmt, result::Result};
 
 #[derive(Debug, Default, Clone)]
 pub enum Mode {
@@ -20,6 +37,7 @@ pub enum Mode {
     VisualLine,
     Insert,
     Command,
+    Search,
     Pending,
 }
 
@@ -29,6 +47,7 @@ impl fmt::Display for Mode {
             Self::Normal => "NORMAL",
             Self::Insert => "INSERT",
             Self::Command => "COMMAND",
+            Self::Search => "SEARCH",
             Self::Visual => "VISUAL",
             Self::VisualBlock => "V-BLOCK",
             Self::VisualLine => "V-LINE",
@@ -39,6 +58,13 @@ impl fmt::Display for Mode {
     }
 }
 
+// a line shown in the footer until the next key press
+#[derive(Debug, Clone)]
+pub struct Message {
+    pub text: String,
+    pub error: bool,
+}
+
 /// Application result type.
 pub type AppResult<T> = Result<T, Box<dyn error::Error>>;
 
@@ -50,8 +76,14 @@ pub struct App<'a> {
     pub child_preview: String,
     pub buffer_storage: BufferStorage,
     pub yank_buffer: YankBuffer,
-    pub undo_stack: UndoStack,
-    pub command: Option<String>,
+    pub undo_tree: UndoTree,
+    pub change_recorder: ChangeRecorder,
+    pub macros: Macros,
+    pub options: Options,
+    pub command_line: CommandLine,
+    pub search: Search,
+    pub commands: CommandRegistry,
+    pub message: Option<Message>,
     pub path: PathHelper,
 
     pub parent_pane: Option<Paragraph<'a>>,
@@ -60,16 +92,45 @@ pub struct App<'a> {
 
     pub cursor: Cursor,
     pub command_buffer: InputBuffer,
+    pub visual_start: Option<(u16, u16)>,
+    pub visual_marks: Option<(usize, usize)>,
 
     pub need_confirmation: bool,
+    pub quit_after_save: bool,
     pub rerender_dir_content: bool,
+    // entry the cursor lands on once the listing is rendered again
+    pub cursor_target: Option<String>,
+    pub previous_dir: Option<String>,
+    pub popup: Option<Popup>,
+    pub finder: Option<Finder>,
+    pub jump_list: JumpList,
+    pub marks: Marks,
+    pub tabs: Tabs,
+    pub dual_pane: Option<DualPane>,
+    // the cursor position left behind in every directory shown so far
+    pub positions: HashMap<String, DirPosition>,
+    // a file to open in the editor, the main loop hands the terminal over
+    pub pending_open: Option<String>,
 }
 
 impl Default for App<'_> {
     fn default() -> Self {
         let mut buffer_storage = BufferStorage::new();
+        buffer_storage.frecency = Frecency::load();
         let _ = buffer_storage.add_view(system::pwd());
 
+        let options = Options::load();
+        let macros = match options.persist_macros {
+            true => Macros::load(),
+            false => Macros::new(),
+        };
+
+        let mut command_registry = CommandRegistry::new();
+        commands::register_defaults(&mut command_registry);
+        substitute::register(&mut command_registry);
+        global::register(&mut command_registry);
+        shell::register(&mut command_registry);
+
         Self {
             running: true,
             mode: Mode::default(),
@@ -77,8 +138,14 @@ impl Default for App<'_> {
             child_preview: String::from(""),
             buffer_storage: buffer_storage,
             yank_buffer: YankBuffer::new(),
-            undo_stack: UndoStack::new(),
-            command: None,
+            undo_tree: UndoTree::new(),
+            change_recorder: ChangeRecorder::new(),
+            macros,
+            options,
+            command_line: CommandLine::new("history"),
+            search: Search::new(),
+            commands: command_registry,
+            message: None,
             path: PathHelper::new("./", &system::pwd()),
 
             parent_pane: None,
@@ -87,19 +154,181 @@ impl Default for App<'_> {
 
             cursor: Cursor::default(),
             command_buffer: InputBuffer::new(),
+            visual_start: None,
+            visual_marks: None,
             need_confirmation: false,
+            quit_after_save: false,
             rerender_dir_content: true,
+            cursor_target: None,
+            previous_dir: None,
+            popup: None,
+            finder: None,
+            jump_list: JumpList::new(&system::pwd()),
+            marks: Marks::load(),
+            tabs: Tabs::new(),
+            dual_pane: None,
+            positions: HashMap::new(),
+            pending_open: None,
         }
     }
 }
 
 impl App<'_> {
-    pub fn tick(&self) {}
+    pub fn tick(&mut self) {
+        if let Some(finder) = &mut self.finder {
+            finder.poll();
+        }
+
+        // a failed write only costs the ranking the visits since the last one
+        let _ = self.buffer_storage.frecency.flush();
+    }
 
     pub fn quit(&mut self) {
         self.running = false;
     }
 
+    pub fn echo(&mut self, text: &str) {
+        self.message = Some(Message {
+            text: text.to_string(),
+            error: false,
+        });
+    }
+
+    pub fn echo_error(&mut self, text: &str) {
+        self.message = Some(Message {
+            text: text.to_string(),
+            error: true,
+        });
+    }
+
+    // lines covered by the visual selection, 1-based and inclusive
+    pub fn visual_selection(&self) -> Option<(usize, usize)> {
+        let (_, start) = self.visual_start?;
+        let (start, end) = (start as usize, self.cursor.y as usize);
+
+        Some((start.min(end), start.max(end)))
+    }
+
+    pub fn end_visual(&mut self) {
+        if let Some(selection) = self.visual_selection() {
+            self.visual_marks = Some(selection);
+        }
+
+        self.visual_start = None;
+    }
+
+    // the line a mark is on in the current listing, marks on entries that
+    // are gone are not set anymore
+    pub fn get_mark(&self, mark: char) -> Option<usize> {
+        let dir = self.path.get_absolute_path();
+        let line_of = |entry: &str| {
+            self.buffer_content
+                .lines()
+                .position(|line| line.trim() == entry)
+                .map(|index| index + 1)
+        };
+
+        match mark {
+            '<' => self.visual_marks.map(|(start, _)| start),
+            '>' => self.visual_marks.map(|(_, end)| end),
+            'a'..='z' => line_of(self.marks.local(&dir, mark)?),
+            'A'..='Z' => {
+                let found = self.marks.global(mark).filter(|found| found.dir == dir)?;
+                line_of(&found.entry).or(Some(found.line as usize))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn set_mark(&mut self, mark: char) -> Result<(), String> {
+        if !Marks::is_valid(mark) {
+            return Err(String::from(
+                "E191: Argument must be a letter or forward/backward quote",
+            ));
+        }
+
+        let entry = self
+            .buffer_content
+            .lines()
+            .nth(self.cursor.y.max(1) as usize - 1)
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+
+        let found = Mark {
+            dir: self.path.get_absolute_path(),
+            line: self.cursor.y,
+            entry,
+        };
+
+        self.marks
+            .set(mark, found)
+            .map_err(|error| format!("Cannot save bookmarks: {}", error))
+    }
+
+    // 'a goes to a marked entry, 'A to the directory of a bookmark first
+    pub fn jump_to_mark(&mut self, mark: char) -> Result<(), String> {
+        if let Some(found) = self.marks.global(mark).cloned() {
+            if found.dir != self.path.get_absolute_path() {
+                self.open_dir(&found.dir)?;
+                self.cursor.move_to(1, found.line);
+                self.cursor_target = Some(found.entry).filter(|entry| !entry.is_empty());
+
+                return Ok(());
+            }
+        }
+
+        let line = self
+            .get_mark(mark)
+            .ok_or_else(|| String::from("E20: Mark not set"))?;
+
+        self.cursor.y = line as u16;
+        self.cursor.reset_x();
+
+        Ok(())
+    }
+
+    // a pattern that is still being typed may not compile yet
+    pub fn search_regex(&self, pattern: &str) -> Option<Regex> {
+        search::compile(pattern, self.options.ignore_case, self.options.smart_case).ok()
+    }
+
+    // n and N, `forward` is relative to the buffer, not to the last search
+    pub fn search_next(&mut self, forward: bool) -> Result<(), String> {
+        let pattern = self
+            .search
+            .pattern
+            .clone()
+            .ok_or(String::from("E35: No previous regular expression"))?;
+        let regex = search::compile(&pattern, self.options.ignore_case, self.options.smart_case)?;
+
+        self.search.highlight = true;
+
+        let position = (
+            self.cursor.y as usize - 1,
+            self.cursor.x.max(1) as usize - 1,
+        );
+        let ((row, col), wrapped) = search::find(&self.buffer_content, &regex, position, forward)
+            .ok_or(format!("E486: Pattern not found: {}", pattern))?;
+
+        self.cursor.y = row as u16 + 1;
+        self.cursor.x = col as u16 + 1;
+
+        if wrapped {
+            self.echo_error(match forward {
+                true => "search hit BOTTOM, continuing at TOP",
+                false => "search hit TOP, continuing at BOTTOM",
+            });
+        }
+
+        Ok(())
+    }
+
+    // normal mode without a half typed command, changes start and end here
+    pub fn is_idle(&self) -> bool {
+        matches!(self.mode, Mode::Normal) && self.command_buffer.buffer.is_empty()
+    }
+
     pub fn set_mode(&mut self, mode: Mode) -> Result<(), Box<dyn std::error::Error>> {
         self.mode = mode.clone();
 
@@ -134,25 +363,34 @@ impl App<'_> {
 
         let line = &mut lines[y as usize];
 
-        if x as usize > line.len() {
-            return;
-        }
+        let offset = match byte_offset(line, x as usize) {
+            Some(offset) => offset,
+            None => return,
+        };
 
         let identifier = line.clone();
+        // an entry keeps its directory when its line gets split
+        let dir = self.dir_of(&lines, y as usize);
 
-        line.insert_str(x as usize, content);
+        let line = &mut lines[y as usize];
+        line.insert_str(offset, content);
+        let line = line.clone();
 
-        let view = self.buffer_storage.get_view(&self.path.get_absolute_path());
+        // a new one goes into the branch its indentation puts it in
+        let dir = match identifier.trim().is_empty() {
+            true => self.dir_of(&lines, y as usize),
+            false => dir,
+        };
+        let view = self.buffer_storage.get_view(&dir);
         match view {
             Some(mut view) => {
                 if identifier.trim().is_empty() {
-                    view.add_file(line, FileType::File);
+                    view.add_file(line.trim(), FileType::from_name(line.trim()));
                 } else {
-                    view.set_name(&identifier, line);
+                    view.set_name(identifier.trim(), line.trim());
                 }
 
-                self.buffer_storage
-                    .update_view(&self.path.get_absolute_path(), view);
+                self.buffer_storage.update_view(&dir, view);
             }
             _ => {}
         }
@@ -167,19 +405,22 @@ impl App<'_> {
         }
 
         let line = &mut lines[y as usize];
-        if x as usize >= line.len() {
-            return;
-        }
+        let offset = match byte_offset(line, x as usize) {
+            Some(offset) if offset < line.len() => offset,
+            _ => return,
+        };
         let identifier = line.clone();
+        let dir = self.dir_of(&lines, y as usize);
 
-        line.remove(x as usize);
+        let line = &mut lines[y as usize];
+        line.remove(offset);
+        let line = line.clone();
 
-        let view = self.buffer_storage.get_view(&self.path.get_absolute_path());
+        let view = self.buffer_storage.get_view(&dir);
         match view {
             Some(mut view) => {
-                view.set_name(&identifier, line);
-                self.buffer_storage
-                    .update_view(&self.path.get_absolute_path(), view);
+                view.set_name(identifier.trim(), line.trim());
+                self.buffer_storage.update_view(&dir, view);
             }
             _ => {}
         }
@@ -207,6 +448,124 @@ impl App<'_> {
         self.buffer_content = lines.join("\n");
     }
 
+    // replaces a whole line and keeps the view in sync, an emptied line counts
+    // as a deleted file just like `cc`
+    pub fn replace_line(&mut self, y: u16, content: &str) {
+        let mut lines: Vec<String> = self.buffer_content.lines().map(String::from).collect();
+
+        if y as usize >= lines.len() {
+            return;
+        }
+
+        let identifier = lines[y as usize].clone();
+        let dir = self.dir_of(&lines, y as usize);
+        lines[y as usize] = content.to_string();
+
+        if let Some(mut view) = self.buffer_storage.get_view(&dir) {
+            if content.trim().is_empty() {
+                view.set_state(identifier.trim(), State::Deleted);
+            } else {
+                view.set_name(identifier.trim(), content.trim());
+            }
+
+            self.buffer_storage.update_view(&dir, view);
+        }
+
+        self.buffer_content = lines.join("\n");
+    }
+
+    // inserts a new line before line y, pasting a name that was deleted from
+    // this directory restores it, any other name becomes a new file
+    pub fn insert_line(&mut self, y: u16, content: &str) {
+        let mut lines: Vec<String> = self.buffer_content.lines().map(String::from).collect();
+        let y = (y as usize).min(lines.len());
+
+        lines.insert(y, content.to_string());
+
+        let dir = self.dir_of(&lines, y);
+        if let Some(mut view) = self.buffer_storage.get_view(&dir) {
+            let name = content.trim();
+
+            match view.get_file(name) {
+                Some(_) => view.set_path(name, &dir),
+                None if !name.is_empty() => view.add_file(name, FileType::from_name(name)),
+                None => {}
+            }
+
+            self.buffer_storage.update_view(&dir, view);
+        }
+
+        self.buffer_content = lines.join("\n");
+    }
+
+    // a line yanked in another directory brings its entry along: one that
+    // was deleted there is moved here, anything else is copied. In tree mode
+    // the line takes the indentation of the branch it is pasted into
+    pub fn paste_line(&mut self, y: u16, content: &str, dir: Option<&str>) -> Result<(), String> {
+        let mut lines: Vec<String> = self.buffer_content.lines().map(String::from).collect();
+        let y = (y as usize).min(lines.len());
+        let name = content.trim();
+
+        let content = match self.options.tree {
+            true => tree::indentation_at(&lines, y) + name,
+            false => content.to_string(),
+        };
+        lines.insert(y, content.clone());
+        let target = self.dir_of(&lines, y);
+
+        let source = match dir {
+            Some(dir) if dir != target && !name.is_empty() && name != "../" => dir,
+            _ => {
+                self.insert_line(y as u16, &content);
+                return Ok(());
+            }
+        };
+
+        let exists = self
+            .buffer_storage
+            .views
+            .get(&target)
+            .and_then(|view| view.get_file(name))
+            .is_some_and(|file| file.state != State::Deleted);
+        if exists {
+            return Err(format!("E13: File exists: {}", name));
+        }
+
+        let _ = self.buffer_storage.add_view(source.to_string());
+        let file = match self
+            .buffer_storage
+            .views
+            .get(source)
+            .and_then(|view| view.get_file(name))
+        {
+            Some(file) => file,
+            None => {
+                self.insert_line(y as u16, &content);
+                return Ok(());
+            }
+        };
+
+        // an entry can only be moved away once, pasting it again copies it
+        let moved_already = self
+            .buffer_storage
+            .views
+            .values()
+            .flat_map(|view| view.files.values())
+            .any(|other| {
+                matches!(other.state, State::Moved | State::Modified)
+                    && other.original_path() == file.original_path()
+            });
+
+        match self.buffer_storage.get_view_mut(&target) {
+            Some(view) => view.add_entry(&file, file.state == State::Deleted && !moved_already),
+            None => return Err(String::from("E499: Directory is not loaded")),
+        }
+
+        self.buffer_content = lines.join("\n");
+
+        Ok(())
+    }
+
     pub fn delete_line_full(&mut self, y: u16) {
         let mut lines: Vec<&str> = self.buffer_content.lines().collect();
 
@@ -219,6 +578,47 @@ impl App<'_> {
         self.buffer_content = lines.join("\n");
     }
 
+    // removes the 0-based lines start..=end and marks their files for
+    // deletion, the removed lines are returned with their directories for
+    // the registers. In tree mode an open directory takes its whole branch
+    // along, entries nested under another line of the range go with it
+    // rather than on their own
+    pub fn delete_lines(&mut self, start: usize, end: usize) -> Vec<(String, String)> {
+        let mut lines: Vec<String> = self.buffer_content.lines().map(String::from).collect();
+
+        if start > end || start >= lines.len() {
+            return vec![];
+        }
+
+        let end = end.min(lines.len() - 1);
+
+        let mut removed = vec![];
+        let mut branch_end = start;
+        for y in start..=end {
+            if y < branch_end {
+                continue;
+            }
+
+            removed.push((lines[y].clone(), self.dir_of(&lines, y)));
+            branch_end = match self.options.tree {
+                true => tree::branch_end(&lines, y),
+                false => y + 1,
+            };
+        }
+
+        lines.drain(start..branch_end.max(end + 1));
+
+        for (line, dir) in &removed {
+            if let Some(view) = self.buffer_storage.get_view_mut(dir) {
+                view.set_state(line.trim(), State::Deleted);
+            }
+        }
+
+        self.buffer_content = lines.join("\n");
+
+        removed
+    }
+
     pub fn delete_line(&mut self, y: u16) {
         let mut lines: Vec<String> = self
             .buffer_content
@@ -230,7 +630,9 @@ impl App<'_> {
             return;
         }
 
-        lines[y as usize].clear();
+        // a nested entry stays in its branch
+        let indentation = tree::indent(&lines[y as usize]);
+        lines[y as usize].truncate(indentation);
 
         self.buffer_content = lines.join("\n");
     }
@@ -419,24 +821,157 @@ impl App<'_> {
         end
     }
 
-    // both of the following hurt me to re-implement here but it's necessary
-    // to update the buffer content
-    pub fn undo(&mut self) {
-        if let Some(undo) = self.undo_stack.undo() {
-            self.buffer_content = undo;
-            if let Some((x, y)) = self.undo_stack.get_pointers() {
-                self.cursor.x = x as u16;
-                self.cursor.y = y as u16;
-            }
+    // the directory the entry on a 0-based line is in, in tree mode the one
+    // it is indented under
+    pub fn line_dir(&self, y: usize) -> String {
+        let lines: Vec<&str> = self.buffer_content.lines().collect();
+        self.dir_of(&lines, y)
+    }
+
+    // where the entry on a 0-based line is on disk right now
+    pub fn entry_path(&self, y: usize) -> String {
+        let lines: Vec<&str> = self.buffer_content.lines().collect();
+        self.path_of(&lines, y)
+    }
+
+    pub fn dir_of<S: AsRef<str>>(&self, lines: &[S], y: usize) -> String {
+        let parent = match self.options.tree {
+            true => tree::parent_line(lines, y),
+            false => None,
+        };
+
+        match parent {
+            Some(parent) => self.path_of(lines, parent),
+            None => self.path.get_absolute_path(),
         }
     }
 
-    pub fn redo(&mut self) {
-        if let Some(redo) = self.undo_stack.redo() {
-            self.buffer_content = redo;
+    // a directory that was renamed or moved here is still found under its
+    // old path until the changes are written
+    fn path_of<S: AsRef<str>>(&self, lines: &[S], y: usize) -> String {
+        let dir = self.dir_of(lines, y);
+        let name = lines
+            .get(y)
+            .map(|line| line.as_ref().trim())
+            .unwrap_or_default();
+
+        match self
+            .buffer_storage
+            .views
+            .get(&dir)
+            .and_then(|view| view.get_file(name))
+        {
+            Some(file) => file.original_path().trim_end_matches('/').to_string(),
+            None => format!("{}/{}", dir.trim_end_matches('/'), name),
+        }
+    }
+
+    // the indentation a line opened at the 0-based `index` starts with
+    pub fn indentation_at(&self, index: usize) -> String {
+        let lines: Vec<&str> = self.buffer_content.lines().collect();
+
+        match self.options.tree {
+            true => tree::indentation_at(&lines, index),
+            false => String::new(),
+        }
+    }
+
+    // the views are left out, the buffer storage keeps the ones a change
+    // touches until it is closed
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            buffer_content: self.buffer_content.clone(),
+            views: HashMap::new(),
+            path: self.path.get_absolute_path(),
+            cursor: (self.cursor.x, self.cursor.y),
+        }
+    }
+
+    // the key handler opens a change on every key it gets while idle
+    pub fn begin_change(&mut self) {
+        if self.undo_tree.begin(self.snapshot()) {
+            self.buffer_storage.take_touched();
         }
     }
 
+    // closes the change the key handler started, views that were only loaded
+    // in the meantime don't count as a change
+    pub fn commit_change(&mut self) {
+        let mut before = match self.undo_tree.take_pending() {
+            Some(before) => before,
+            None => return,
+        };
+
+        // a node only holds the views its change touched, so moving through
+        // the history of one tab leaves what other tabs did alone
+        let touched = self.buffer_storage.take_touched();
+        let after_views: HashMap<String, DirBuffer> = touched
+            .iter()
+            .filter_map(|(dir, view)| {
+                let current = self.buffer_storage.views.get(dir)?;
+                (current != view).then(|| (dir.clone(), current.clone()))
+            })
+            .collect();
+
+        if !after_views.is_empty() || before.buffer_content != self.buffer_content {
+            before.views = touched
+                .into_iter()
+                .filter(|(dir, _)| after_views.contains_key(dir))
+                .collect();
+
+            let mut after = self.snapshot();
+            after.views = after_views;
+
+            self.undo_tree.push(before, after);
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        let current = self.path.get_absolute_path();
+        if snapshot.path != current {
+            let _ = self.path.set_path(&snapshot.path);
+        }
+
+        self.buffer_content = snapshot.buffer_content;
+        self.buffer_storage.views.extend(snapshot.views);
+        let _ = self.buffer_storage.add_view(snapshot.path);
+
+        self.cursor.x = snapshot.cursor.0;
+        self.cursor.y = snapshot.cursor.1;
+        self.rerender_dir_content = false;
+    }
+
+    // the undo tree is driven by the key handler, moving through it is not a
+    // change of its own, neither for undo nor for `.`
+    fn travel(&mut self, snapshots: impl IntoIterator<Item = Snapshot>) {
+        self.undo_tree.cancel();
+        self.change_recorder.discard();
+
+        for snapshot in snapshots {
+            self.restore(snapshot);
+        }
+    }
+
+    pub fn undo(&mut self) {
+        let snapshot = self.undo_tree.undo();
+        self.travel(snapshot);
+    }
+
+    pub fn redo(&mut self) {
+        let snapshot = self.undo_tree.redo();
+        self.travel(snapshot);
+    }
+
+    pub fn undo_earlier(&mut self) {
+        let snapshot = self.undo_tree.earlier();
+        self.travel(snapshot);
+    }
+
+    pub fn undo_later(&mut self) {
+        let snapshot = self.undo_tree.later();
+        self.travel(snapshot);
+    }
+
     pub fn get_files(&self, state: State) -> Vec<FileEntry> {
         let views = self.buffer_storage.views.clone();
         let mut files = Vec::new();
@@ -451,6 +986,375 @@ impl App<'_> {
         files
     }
 
+    // every directory change goes through here, `path` may be relative to
+    // the current directory and use `~` or environment variables
+    pub fn open_dir(&mut self, path: &str) -> Result<(), String> {
+        self.enter_dir(path)?;
+        self.jump_list.record(&self.path.get_absolute_path());
+
+        Ok(())
+    }
+
+    // Ctrl-o and Ctrl-i, nothing happens at either end of the jump list
+    pub fn jump(&mut self, forward: bool) -> Result<(), String> {
+        let target = match forward {
+            true => self.jump_list.forward(),
+            false => self.jump_list.back(),
+        };
+
+        match target {
+            Some(target) => self.enter_dir(&target),
+            None => Ok(()),
+        }
+    }
+
+    fn enter_dir(&mut self, path: &str) -> Result<(), String> {
+        let current = self.path.get_absolute_path();
+        let target = PathHelper::expand(path, &current);
+
+        if !target.is_dir() {
+            return Err(format!("E344: Can't find directory \"{}\"", path));
+        }
+
+        let target = target.to_string_lossy().to_string();
+        self.path
+            .set_path(&target)
+            .map_err(|_| format!("E344: Can't find directory \"{}\"", path))?;
+        self.buffer_storage
+            .add_view(target.clone())
+            .map_err(|error| error.to_string())?;
+
+        self.remember_position(&current);
+        self.restore_position(&target);
+        self.rerender_dir_content = true;
+
+        // going up keeps the directory we came from under the cursor
+        let current_path = std::path::Path::new(&current);
+        if current_path.parent() == Some(std::path::Path::new(&target)) {
+            self.cursor_target = current_path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string());
+        }
+
+        if target != current {
+            self.previous_dir = Some(current);
+        }
+
+        Ok(())
+    }
+
+    // the parts of the state that belong to the active tab, a change that
+    // is still open can't carry over into another tab
+    fn take_tab(&mut self) -> Tab {
+        self.undo_tree.cancel();
+        self.change_recorder.discard();
+        self.remember_position(&self.path.get_absolute_path());
+
+        Tab {
+            path: self.path.clone(),
+            buffer_content: std::mem::take(&mut self.buffer_content),
+            cursor: (self.cursor.x, self.cursor.y, self.cursor.scroll),
+            undo_tree: std::mem::take(&mut self.undo_tree),
+            jump_list: std::mem::take(&mut self.jump_list),
+            previous_dir: self.previous_dir.take(),
+        }
+    }
+
+    fn load_tab(&mut self, tab: Tab) {
+        self.path = tab.path;
+        self.buffer_content = tab.buffer_content;
+        (self.cursor.x, self.cursor.y, self.cursor.scroll) = tab.cursor;
+        self.undo_tree = tab.undo_tree;
+        self.jump_list = tab.jump_list;
+        self.previous_dir = tab.previous_dir;
+
+        self.visual_marks = None;
+        self.cursor_target = None;
+        self.rerender_dir_content = false;
+    }
+
+    // `:tabnew`, the new tab starts in `path` or where the current one is
+    pub fn new_tab(&mut self, path: Option<&str>) -> Result<(), String> {
+        let current = self.path.get_absolute_path();
+        let target = PathHelper::expand(path.unwrap_or(&current), &current);
+
+        if !target.is_dir() {
+            return Err(format!(
+                "E344: Can't find directory \"{}\"",
+                path.unwrap_or_default()
+            ));
+        }
+
+        let tab = self.take_tab();
+        self.tabs.open(tab);
+
+        self.jump_list = JumpList::new(&current);
+        self.rerender_dir_content = true;
+        self.open_dir(&target.to_string_lossy())
+    }
+
+    // 0-based, anything out of range is ignored
+    pub fn switch_tab(&mut self, index: usize) {
+        if index == self.tabs.current || index >= self.tabs.count() {
+            return;
+        }
+
+        let tab = self.take_tab();
+        if let Some(tab) = self.tabs.switch(tab, index) {
+            self.load_tab(tab);
+        }
+    }
+
+    // gt and gT wrap around
+    pub fn next_tab(&mut self, forward: bool) {
+        let count = self.tabs.count();
+        let index = match forward {
+            true => (self.tabs.current + 1) % count,
+            false => (self.tabs.current + count - 1) % count,
+        };
+
+        self.switch_tab(index);
+    }
+
+    // pending changes made in the tab stay until they are written
+    pub fn close_tab(&mut self) -> Result<(), String> {
+        if self.tabs.count() < 2 {
+            return Err(String::from("E784: Cannot close last tab page"));
+        }
+
+        self.remember_position(&self.path.get_absolute_path());
+        if let Some(tab) = self.tabs.close() {
+            self.undo_tree.cancel();
+            self.change_recorder.discard();
+            self.load_tab(tab);
+        }
+
+        Ok(())
+    }
+
+    // `:dual`, the other side starts in `path` or where the focused one is
+    pub fn open_dual_pane(&mut self, path: Option<&str>) -> Result<(), String> {
+        let current = self.path.get_absolute_path();
+        let target = PathHelper::expand(path.unwrap_or(&current), &current);
+
+        if !target.is_dir() {
+            return Err(format!(
+                "E344: Can't find directory \"{}\"",
+                path.unwrap_or_default()
+            ));
+        }
+
+        let target = target.to_string_lossy().to_string();
+        let mut other_path = self.path.clone();
+        other_path
+            .set_path(&target)
+            .map_err(|_| format!("E344: Can't find directory \"{}\"", target))?;
+        self.buffer_storage
+            .add_view(target.clone())
+            .map_err(|error| error.to_string())?;
+
+        let listing = self.listing(&other_path);
+        let position = self.positions.get(&target).cloned().unwrap_or_default();
+        let cursor = (
+            position.x,
+            position.y.min(listing.len() as u16),
+            position.scroll,
+        );
+
+        // the focused listing stays on its side when the other one changes
+        let focus_left = self.dual_pane.as_ref().is_none_or(|pane| pane.focus_left);
+
+        let mut pane = DualPane::new(Tab {
+            path: other_path,
+            buffer_content: listing.join("\n"),
+            cursor,
+            undo_tree: UndoTree::new(),
+            jump_list: JumpList::new(&target),
+            previous_dir: None,
+        });
+        pane.focus_left = focus_left;
+        self.dual_pane = Some(pane);
+
+        Ok(())
+    }
+
+    // pending changes made on the other side stay until they are written
+    pub fn close_dual_pane(&mut self) {
+        self.dual_pane = None;
+    }
+
+    // the other side is listed again when its directory was changed from
+    // the focused one, like when both show the same directory
+    pub fn refresh_other_pane(&mut self) {
+        let Some(pane) = &self.dual_pane else {
+            return;
+        };
+
+        let dir = pane.other.path.get_absolute_path();
+        let Some(names) = self
+            .buffer_storage
+            .views
+            .get(&dir)
+            .map(|view| view.listed_names())
+        else {
+            return;
+        };
+
+        // nested tree branches belong to other views
+        let mut shown: Vec<&str> = pane
+            .other
+            .buffer_content
+            .lines()
+            .skip(1)
+            .filter(|line| tree::indent(line) == 0 && !line.trim().is_empty())
+            .map(str::trim)
+            .collect();
+        shown.sort();
+
+        let mut expected: Vec<&str> = names.iter().map(String::as_str).collect();
+        expected.sort();
+
+        if shown == expected {
+            return;
+        }
+
+        let listing: Vec<&str> = std::iter::once("../")
+            .chain(names.iter().map(String::as_str))
+            .collect();
+        if let Some(pane) = &mut self.dual_pane {
+            pane.other.buffer_content = listing.join("\n");
+            pane.other.cursor.1 = pane.other.cursor.1.min(listing.len() as u16);
+        }
+    }
+
+    // Ctrl-w, the focus goes over to the other side
+    pub fn switch_pane(&mut self) {
+        if self.dual_pane.is_none() {
+            return;
+        }
+
+        self.refresh_other_pane();
+
+        let active = self.take_tab();
+        if let Some(other) = self.dual_pane.as_mut().map(|pane| pane.switch(active)) {
+            self.load_tab(other);
+        }
+    }
+
+    // coming back to a directory puts the cursor where it was left
+    fn remember_position(&mut self, dir: &str) {
+        // the listing still belongs to the previous directory until it has
+        // been drawn, the stored position is still the right one then
+        if self.rerender_dir_content {
+            return;
+        }
+
+        let hovered = self
+            .buffer_content
+            .lines()
+            .nth(self.cursor.y.max(1) as usize - 1)
+            .map(|line| line.trim().to_string());
+
+        self.positions.insert(
+            dir.to_string(),
+            DirPosition {
+                x: self.cursor.x,
+                y: self.cursor.y,
+                hovered,
+                scroll: self.cursor.scroll,
+            },
+        );
+    }
+
+    fn restore_position(&mut self, dir: &str) {
+        let position = self.positions.get(dir).cloned().unwrap_or_default();
+
+        self.cursor.move_to(position.x, position.y);
+        self.cursor.scroll = position.scroll;
+        // the entry may have moved if files were added or removed meanwhile
+        self.cursor_target = position.hovered;
+    }
+
+    // forgets every pending change and the undo history made of them, and
+    // reads the current directory again. The directories seen so far keep
+    // their cursor positions
+    pub fn reload(&mut self) {
+        let current = self.path.get_absolute_path();
+        self.remember_position(&current);
+
+        let previous = std::mem::replace(&mut self.buffer_storage, BufferStorage::new());
+        let _ = self.buffer_storage.add_view(current.clone());
+
+        // views that were only previewed are read again when needed
+        for dir in previous.views.into_keys() {
+            if self.positions.contains_key(&dir) && dir != current {
+                let _ = self.buffer_storage.add_view(dir);
+            }
+        }
+
+        // the other tabs and the other side of the dual pane are listed again,
+        // none of them can undo back to changes that are gone
+        self.undo_tree = UndoTree::new();
+        let paths: Vec<PathHelper> = self
+            .tabs
+            .stored_mut()
+            .chain(self.dual_pane.iter_mut().map(|pane| &mut pane.other))
+            .map(|tab| tab.path.clone())
+            .collect();
+        let listings: Vec<Vec<String>> = paths
+            .iter()
+            .map(|path| {
+                let _ = self.buffer_storage.add_view(path.get_absolute_path());
+                self.listing(path)
+            })
+            .collect();
+
+        for (tab, listing) in self
+            .tabs
+            .stored_mut()
+            .chain(self.dual_pane.iter_mut().map(|pane| &mut pane.other))
+            .zip(listings)
+        {
+            tab.buffer_content = listing.join("\n");
+            tab.cursor.1 = tab.cursor.1.min(listing.len() as u16);
+            tab.undo_tree = UndoTree::new();
+        }
+
+        // reading the same directories again isn't a visit
+        self.buffer_storage.frecency = previous.frecency;
+
+        self.restore_position(&current);
+        self.rerender_dir_content = true;
+    }
+
+    // the entries of `dir` the way they are listed, directories first and
+    // "../" on top
+    pub fn listing(&self, dir: &PathHelper) -> Vec<String> {
+        let absolute = dir.get_absolute_path();
+
+        let mut files = dir
+            .get_dir_names_printable(true)
+            .unwrap_or(vec![])
+            .iter()
+            .map(|s| PathHelper::trim_path(s.as_str()))
+            .collect::<Vec<_>>();
+
+        files.sort_by(|a: &String, b: &String| {
+            let file_type_a = self.get_file_type(&absolute, a);
+            let file_type_b = self.get_file_type(&absolute, b);
+
+            match (file_type_a, file_type_b) {
+                (FileType::Directory, FileType::File) => std::cmp::Ordering::Less,
+                (FileType::File, FileType::Directory) => std::cmp::Ordering::Greater,
+                _ => a.cmp(b),
+            }
+        });
+
+        files.insert(0, String::from("../"));
+
+        files
+    }
+
     pub fn save(&mut self) {
         if self.buffer_storage.has_changes() {
             self.need_confirmation = true;
@@ -471,3 +1375,12 @@ impl App<'_> {
         }
     }
 }
+
+// the cursor counts chars while strings are sliced at bytes, a column past
+// the end of the line has no offset
+fn byte_offset(line: &str, x: usize) -> Option<usize> {
+    line.char_indices()
+        .map(|(offset, _)| offset)
+        .chain(std::iter::once(line.len()))
+        .nth(x)
+}
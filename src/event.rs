@@ -27,12 +27,14 @@ pub struct EventHandler {
     receiver: mpsc::UnboundedReceiver<Event>,
     /// Event handler thread.
     handler: tokio::task::JoinHandle<()>,
+    /// Tick rate, kept to restart the handler.
+    tick_rate: u64,
 }
 
 impl EventHandler {
     /// Constructs a new instance of [`EventHandler`].
-    pub fn new(tick_rate: u64) -> Self {
-        let tick_rate = Duration::from_millis(tick_rate);
+    pub fn new(tick_rate_ms: u64) -> Self {
+        let tick_rate = Duration::from_millis(tick_rate_ms);
         let (sender, receiver) = mpsc::unbounded_channel();
         let _sender = sender.clone();
         let handler = tokio::spawn(async move {
@@ -76,9 +78,20 @@ impl EventHandler {
             sender,
             receiver,
             handler,
+            tick_rate: tick_rate_ms,
         }
     }
 
+    /// Stops reading terminal events, so another program can read them.
+    pub fn stop(&self) {
+        self.handler.abort();
+    }
+
+    /// Constructs a handler with the same tick rate, to start reading again.
+    pub fn restart(&self) -> Self {
+        Self::new(self.tick_rate)
+    }
+
     /// Receive the next event from the handler thread.
     ///
     /// This function will always block the current thread if
@@ -1,10 +1,15 @@
 use crate::app::{App, AppResult, Mode};
 use crate::utils::{
-    buffer_storage::{FileEntry, FileType, State},
+    buffer_storage::{FileType, State},
+    command_line::CommandLine,
+    commands,
+    finder::Finder,
     motion_handler::handler as motion_handler,
-    system,
+    search, system,
+    text_object::TextObject,
+    tree,
 };
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use crossterm::{cursor::SetCursorStyle, execute};
 
 /// Handles the key events and updates the state of [`App`].
@@ -15,25 +20,116 @@ pub fn handle_key_events(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
         return Ok(());
     }
 
+    app.message = None;
+
+    if let Some(popup) = &mut app.popup {
+        let control = key_event.modifiers.contains(KeyModifiers::CONTROL);
+
+        match key_event.code {
+            KeyCode::Char('d') | KeyCode::Char('x') if !control && popup.on_delete.is_some() => {
+                if let (Some(on_delete), Some(line)) = (popup.on_delete, popup.selected_line()) {
+                    let line = line.clone();
+                    on_delete(app, &line);
+                }
+            }
+            KeyCode::Char('j') | KeyCode::Down if !control => popup.scroll_down(1),
+            KeyCode::Char('k') | KeyCode::Up if !control => popup.scroll_up(1),
+            KeyCode::Char('d') if control => popup.scroll_down(10),
+            KeyCode::Char('u') if control => popup.scroll_up(10),
+            KeyCode::PageDown => popup.scroll_down(20),
+            KeyCode::PageUp => popup.scroll_up(20),
+            KeyCode::Char('g') => popup.scroll_to_start(),
+            KeyCode::Char('G') => popup.scroll_to_end(),
+            _ => app.popup = None,
+        }
+
+        return Ok(());
+    }
+
+    if app.finder.is_some() {
+        return handle_finder(key_event, app);
+    }
+
+    let idle = app.is_idle();
+    if idle && app.macros.is_recording() && key_event.code == KeyCode::Char('q') {
+        app.macros.stop();
+        if app.options.persist_macros {
+            if let Err(error) = app.macros.save() {
+                app.echo_error(&format!("Cannot save macros: {}", error));
+            }
+        }
+
+        return Ok(());
+    }
+    app.macros.record(key_event);
+
+    if idle && key_event.code == KeyCode::Char('.') {
+        return repeat_last_change(app);
+    }
+
+    if idle {
+        app.change_recorder.begin(&app.buffer_content);
+        app.begin_change();
+    }
+    app.change_recorder.record(key_event);
+
     let result = match app.mode {
         Mode::Normal => handle_normal_mode(key_event, app),
         Mode::Insert => handle_insert_mode(key_event, app),
         Mode::Command => handle_command_mode(key_event, app),
+        Mode::Search => handle_search_mode(key_event, app),
         Mode::Visual => handle_visual_mode(key_event, app),
         Mode::VisualBlock => handle_visual_block_mode(key_event, app),
         Mode::VisualLine => handle_visual_line_mode(key_event, app),
         Mode::Pending => handle_pending_mode(key_event, app),
     };
 
+    match app.mode {
+        Mode::Command | Mode::Search | Mode::Visual | Mode::VisualBlock | Mode::VisualLine => {
+            app.change_recorder.discard()
+        }
+        _ if app.is_idle() => app.change_recorder.finish(&app.buffer_content),
+        _ => {}
+    }
+
+    if app.is_idle() {
+        app.commit_change();
+    }
+
+    if let Some(error) = app.yank_buffer.take_clipboard_error() {
+        app.echo_error(&format!("Cannot copy to the clipboard: {}", error));
+    }
+
+    result
+}
+
+fn repeat_last_change(app: &mut App) -> AppResult<()> {
+    let keys = app.change_recorder.last_change();
+
+    // `.` can itself be replayed by :norm or a macro, which are still
+    // running once it is done
+    let replaying = app.change_recorder.replaying;
+    app.change_recorder.replaying = true;
+    let result = keys
+        .into_iter()
+        .try_for_each(|key_event| handle_key_events(key_event, app));
+    app.change_recorder.replaying = replaying;
+
     result
 }
 
 fn handle_normal_mode(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
     if key_event.code == KeyCode::Esc {
         app.command_buffer.clear();
+        app.yank_buffer.deselect();
         return Ok(());
     }
 
+    if key_event.modifiers.contains(KeyModifiers::CONTROL) {
+        app.command_buffer.clear();
+        return handle_control_keys(key_event, app);
+    }
+
     let buffer_empty = app.command_buffer.buffer.is_empty();
     let is_valid_init = app
         .command_buffer
@@ -52,51 +148,202 @@ fn handle_normal_mode(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
         KeyCode::Char('A') => motion_handler::A(app),
         KeyCode::Char('o') => motion_handler::o(app),
         KeyCode::Char('O') => motion_handler::O(app),
-        KeyCode::Char(':') => app.set_mode(Mode::Command)?,
-        KeyCode::Char('v') => app.set_mode(Mode::Visual)?,
-        KeyCode::Char('V') => app.set_mode(Mode::VisualLine)?,
+        KeyCode::Char(':') => {
+            app.command_line.open("");
+            app.set_mode(Mode::Command)?
+        }
+        KeyCode::Char('v') => {
+            app.visual_start = Some((app.cursor.x, app.cursor.y));
+            app.set_mode(Mode::Visual)?
+        }
+        KeyCode::Char('V') => {
+            app.visual_start = Some((app.cursor.x, app.cursor.y));
+            app.set_mode(Mode::VisualLine)?
+        }
         KeyCode::Char('s') => motion_handler::s(app),
+        // search
+        KeyCode::Char('/') => start_search(app, true)?,
+        KeyCode::Char('?') => start_search(app, false)?,
+        KeyCode::Char('n') => {
+            if let Err(error) = app.search_next(app.search.forward) {
+                app.echo_error(&error);
+            }
+        }
+        KeyCode::Char('N') => {
+            if let Err(error) = app.search_next(!app.search.forward) {
+                app.echo_error(&error);
+            }
+        }
+        KeyCode::Char('*') => search_word(app, true)?,
+        KeyCode::Char('#') => search_word(app, false)?,
         // basic movement
         KeyCode::Down => motion_handler::j(app)?,
         KeyCode::Char('j') => motion_handler::j(app)?,
         KeyCode::Up => motion_handler::k(app)?,
         KeyCode::Char('k') => motion_handler::k(app)?,
         KeyCode::Left => app.cursor.left(),
+        KeyCode::Char('h') if app.options.miller && app.cursor.x <= 1 => open_parent(app),
         KeyCode::Char('h') => app.cursor.left(),
         KeyCode::Right => motion_handler::l(app),
+        KeyCode::Char('l') if app.options.miller && at_line_end(app) => open_hovered(app, false),
         KeyCode::Char('l') => motion_handler::l(app),
         // more movement
         KeyCode::Char('0') => app.cursor.reset_x(),
         KeyCode::Char('$') => motion_handler::dollar_sign(app),
         KeyCode::Char('G') => motion_handler::G(app),
+        KeyCode::Char('H') => motion_handler::H(app),
+        KeyCode::Char('M') => motion_handler::M(app),
+        KeyCode::Char('L') => motion_handler::L(app),
         KeyCode::Char('w') => motion_handler::w(app),
+        KeyCode::Char('W') => motion_handler::W(app),
         KeyCode::Char('b') => motion_handler::b(app),
+        KeyCode::Char('B') => motion_handler::B(app),
+        KeyCode::Char('e') => motion_handler::e(app),
+        KeyCode::Char('E') => motion_handler::E(app),
         // other
         KeyCode::Char('x') => motion_handler::x(app),
+        KeyCode::Char('~') => motion_handler::tilde(app),
         KeyCode::Char('u') => motion_handler::u(app),
         KeyCode::Char('p') => motion_handler::p(app),
         KeyCode::Char('P') => motion_handler::P(app),
 
-        KeyCode::Enter => {
-            let line = {
-                let buffer_content = &app.buffer_content;
-                buffer_content
-                    .lines()
-                    .nth(app.cursor.y as usize - 1)
-                    .unwrap_or("")
-                    .to_string()
-            };
-            let _ = app.path.cd(&line);
-            app.buffer_storage.add_view(app.path.get_absolute_path())?;
+        // navigation
+        KeyCode::Enter => open_hovered(app, true),
+        KeyCode::Char('-') | KeyCode::Backspace => open_parent(app),
+        // terminals send Ctrl-i as Tab, so it stays with the jump list even
+        // in tree mode, za toggles a directory there
+        KeyCode::Tab => jump(app, true),
 
-            app.rerender_dir_content = true;
-            app.cursor.reset_x();
-            app.cursor.reset_y();
+        _ => handle_compound_inputs(key_event, app)?,
+    };
+
+    Ok(())
+}
+
+fn hovered_line(app: &App) -> String {
+    app.buffer_content
+        .lines()
+        .nth(app.cursor.y.max(1) as usize - 1)
+        .unwrap_or_default()
+        .trim()
+        .to_string()
+}
+
+fn at_line_end(app: &App) -> bool {
+    app.cursor.x as usize >= hovered_line(app).chars().count()
+}
+
+fn jump(app: &mut App, forward: bool) {
+    if let Err(error) = app.jump(forward) {
+        app.echo_error(&error);
+    }
+}
+
+// zo, zc and za only do something in tree mode
+fn fold(app: &mut App, action: fn(&mut App) -> Result<(), String>) {
+    if !app.options.tree {
+        return;
+    }
+
+    if let Err(error) = action(app) {
+        app.echo_error(&error);
+    }
+}
+
+fn open_parent(app: &mut App) {
+    if let Err(error) = app.open_dir("..") {
+        app.echo_error(&error);
+    }
+}
+
+// directories are opened in place, files in the editor when `files` is set
+fn open_hovered(app: &mut App, files: bool) {
+    let line = hovered_line(app);
+    if line.is_empty() {
+        return;
+    }
+
+    let dir = app.line_dir(app.cursor.y.max(1) as usize - 1);
+    let path = std::path::Path::new(&dir).join(&line);
+
+    match (path.is_dir(), path.is_file()) {
+        (true, _) => {
+            if let Err(error) = app.open_dir(&path.to_string_lossy()) {
+                app.echo_error(&error);
+            }
         }
+        (_, true) if files => app.pending_open = Some(path.to_string_lossy().to_string()),
+        (_, true) => {}
+        _ => app.echo_error(&format!("E447: Can't find file \"{}\"", line)),
+    }
+}
 
-        _ => handle_compound_inputs(key_event, app)?,
+fn handle_control_keys(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
+    match key_event.code {
+        KeyCode::Char('r') => app.redo(),
+        KeyCode::Char('o') => jump(app, false),
+        KeyCode::Char('i') => jump(app, true),
+        KeyCode::Char('p') => app.finder = Some(Finder::new(&app.path.get_absolute_path(), "")),
+        KeyCode::Char('w') => app.switch_pane(),
+        _ => handle_scroll_keys(key_event, app)?,
+    }
+
+    Ok(())
+}
+
+// paging works the same in normal and visual mode
+fn handle_scroll_keys(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
+    match key_event.code {
+        KeyCode::Char('d') => motion_handler::ctrl_d(app),
+        KeyCode::Char('u') => motion_handler::ctrl_u(app),
+        KeyCode::Char('f') => motion_handler::ctrl_f(app),
+        KeyCode::Char('b') => motion_handler::ctrl_b(app),
+        _ => {}
+    }
+
+    Ok(())
+}
+
+// the picker keeps the keys while it is open, arrows and Ctrl-n/Ctrl-p move
+// the selection and Enter jumps to it
+fn handle_finder(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
+    let finder = match &mut app.finder {
+        Some(finder) => finder,
+        None => return Ok(()),
     };
 
+    if cancels_line(&key_event, &finder.line) {
+        app.finder = None;
+        return Ok(());
+    }
+
+    finder.poll();
+
+    let control = key_event.modifiers.contains(KeyModifiers::CONTROL);
+    match key_event.code {
+        KeyCode::Enter => {
+            finder.line.take();
+            let target = finder.target();
+            app.finder = None;
+
+            if let Some((dir, name)) = target {
+                match app.open_dir(&dir) {
+                    Ok(()) => app.cursor_target = Some(name),
+                    Err(error) => app.echo_error(&error),
+                }
+            }
+        }
+        KeyCode::Down | KeyCode::Tab => finder.select_next(),
+        KeyCode::Up | KeyCode::BackTab => finder.select_previous(),
+        KeyCode::Char('j') if control => finder.select_next(),
+        KeyCode::Char('k') if control => finder.select_previous(),
+        // Ctrl-p and Ctrl-n walk through the queries that were used before
+        _ => {
+            edit_line(key_event, &mut finder.line);
+            finder.update();
+        }
+    }
+
     Ok(())
 }
 
@@ -152,110 +399,210 @@ fn handle_insert_mode(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
     Ok(())
 }
 
+// Esc, Ctrl-c and Backspace on an empty line leave the command line
+fn cancels_line(key_event: &KeyEvent, line: &CommandLine) -> bool {
+    let control = key_event.modifiers.contains(KeyModifiers::CONTROL);
+
+    match key_event.code {
+        KeyCode::Esc => true,
+        KeyCode::Char('c') => control,
+        KeyCode::Backspace => line.content.is_empty(),
+        _ => false,
+    }
+}
+
+// editing shared by the command and the search line
+fn edit_line(key_event: KeyEvent, line: &mut CommandLine) {
+    if key_event.modifiers.contains(KeyModifiers::CONTROL) {
+        match key_event.code {
+            KeyCode::Char('w') => line.delete_word(),
+            KeyCode::Char('u') => line.delete_to_start(),
+            KeyCode::Char('h') => line.backspace(),
+            KeyCode::Char('b') => line.home(),
+            KeyCode::Char('e') => line.end(),
+            KeyCode::Char('p') => line.history_older(),
+            KeyCode::Char('n') => line.history_newer(),
+            _ => {}
+        }
+
+        return;
+    }
+
+    match key_event.code {
+        KeyCode::Backspace => line.backspace(),
+        KeyCode::Delete => line.delete(),
+        KeyCode::Left => line.left(),
+        KeyCode::Right => line.right(),
+        KeyCode::Home => line.home(),
+        KeyCode::End => line.end(),
+        KeyCode::Up => line.history_older(),
+        KeyCode::Down => line.history_newer(),
+        KeyCode::Char(c) => line.insert(c),
+        _ => {}
+    }
+}
+
 fn handle_command_mode(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
+    if cancels_line(&key_event, &app.command_line) {
+        app.command_line.close();
+        return app.set_mode(Mode::Normal);
+    }
+
     match key_event.code {
         KeyCode::Enter => {
-            let command = app.command.clone().unwrap_or(String::from(""));
-            match command.as_str() {
-                "q" => {
-                    app.running = false;
-                    return Ok(());
-                }
-                "w" => {
-                    app.save();
-                    app.command = None;
-                    let _ = app.set_mode(Mode::Normal)?;
-                    return Ok(());
-                }
-                "wq" => {
-                    // TODO
-                    // app.save();
-                    app.running = false;
-                    return Ok(());
-                }
-                _ => {
-                    //match Command::new(command).spawn() {
-                    //Ok(_) => app.buffer_content += "success",
-                    //Err(_) => app.buffer_content += "Some error",
-                }
-            }
-
-            app.command = None;
-            app.mode = Mode::Normal;
+            let command = app.command_line.take();
+            app.set_mode(Mode::Normal)?;
 
-            return Ok(());
-        }
-        KeyCode::Backspace => {
-            if let Some(cmd) = &mut app.command {
-                if cmd.len() > 0 {
-                    cmd.pop();
-                }
+            if let Err(error) = commands::execute(app, &command) {
+                app.echo_error(&error);
             }
         }
-        KeyCode::Esc => {
-            app.command = None;
-            app.mode = Mode::Normal;
+        KeyCode::Tab | KeyCode::BackTab => {
+            // candidates are only looked up when a completion starts
+            let candidates = match app.command_line.is_completing() {
+                true => (0, vec![]),
+                false => commands::complete(app, app.command_line.before_cursor()),
+            };
 
-            return Ok(());
+            app.command_line
+                .complete(key_event.code == KeyCode::Tab, candidates);
         }
-        _ => {}
+        _ => edit_line(key_event, &mut app.command_line),
     }
 
-    match &app.command {
-        Some(cmd) => {
-            let code = key_event.code.to_string();
-            if code.len() != 1 || !code.chars().nth(0).unwrap_or(' ').is_alphanumeric() {
-                return Ok(());
-            }
+    Ok(())
+}
 
-            let new_command = cmd.to_owned() + &key_event.code.to_string();
-            app.command = Some(new_command);
+fn start_search(app: &mut App, forward: bool) -> AppResult<()> {
+    app.search.forward = forward;
+    app.search.origin = (app.cursor.x, app.cursor.y);
+    app.search.line.open("");
+
+    app.set_mode(Mode::Search)
+}
+
+// the cursor follows the first match while typing, Enter keeps the pattern
+// for n and N, leaving the line goes back to where the search started
+fn handle_search_mode(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
+    let (x, y) = app.search.origin;
+    app.cursor.x = x;
+    app.cursor.y = y;
+
+    if cancels_line(&key_event, &app.search.line) {
+        app.search.line.close();
+        return app.set_mode(Mode::Normal);
+    }
+
+    if key_event.code == KeyCode::Enter {
+        // an empty pattern repeats the last one
+        let typed = app.search.line.take();
+        if !typed.is_empty() {
+            app.search.pattern = Some(typed);
+        }
+
+        app.set_mode(Mode::Normal)?;
+        if let Err(error) = app.search_next(app.search.forward) {
+            app.echo_error(&error);
         }
-        None => app.command = Some(key_event.code.to_string()),
+
+        return Ok(());
     }
 
-    match key_event.code {
-        _ => {}
-    };
+    edit_line(key_event, &mut app.search.line);
+
+    let origin = (y as usize - 1, x.max(1) as usize - 1);
+    let found = app
+        .search_regex(&app.search.line.content)
+        .and_then(|regex| search::find(&app.buffer_content, &regex, origin, app.search.forward));
+
+    if let Some(((row, col), _)) = found {
+        app.cursor.y = row as u16 + 1;
+        app.cursor.x = col as u16 + 1;
+    }
 
     Ok(())
 }
 
-fn handle_visual_mode(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
-    match key_event.code {
-        KeyCode::Esc => {
-            app.cursor.left();
-            app.set_mode(Mode::Normal)?;
-            let _ = execute!(std::io::stdout(), SetCursorStyle::SteadyBlock);
+// `*` and `#` search for the word under the cursor
+fn search_word(app: &mut App, forward: bool) -> AppResult<()> {
+    let line = app
+        .buffer_content
+        .lines()
+        .nth(app.cursor.y as usize - 1)
+        .unwrap_or_default()
+        .to_string();
+
+    let word = TextObject::Word
+        .find(&line, app.cursor.x.max(1) as usize - 1, false)
+        .map(|(start, end)| {
+            line.chars()
+                .skip(start)
+                .take(end - start)
+                .collect::<String>()
+        })
+        .filter(|word| !word.trim().is_empty());
+
+    match word {
+        Some(word) => {
+            app.search.pattern = Some(format!(r"\b{}\b", regex::escape(&word)));
+            app.search.forward = forward;
+
+            if let Err(error) = app.search_next(forward) {
+                app.echo_error(&error);
+            }
         }
-        _ => {}
-    };
+        None => app.echo_error("E348: No string under cursor"),
+    }
 
     Ok(())
 }
 
-fn handle_visual_block_mode(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
+// movement shared by all visual modes, the selection follows the cursor
+fn handle_visual_motion(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
+    if key_event.modifiers.contains(KeyModifiers::CONTROL) {
+        return handle_scroll_keys(key_event, app);
+    }
+
     match key_event.code {
         KeyCode::Esc => {
+            app.end_visual();
             app.cursor.left();
-            let _ = app.set_mode(Mode::Normal)?;
+            app.set_mode(Mode::Normal)?;
+        }
+        KeyCode::Char(':') => {
+            app.end_visual();
+            app.command_line.open("'<,'>");
+            app.set_mode(Mode::Command)?;
         }
+        KeyCode::Down | KeyCode::Char('j') => motion_handler::j(app)?,
+        KeyCode::Up | KeyCode::Char('k') => motion_handler::k(app)?,
+        KeyCode::Left | KeyCode::Char('h') => app.cursor.left(),
+        KeyCode::Right | KeyCode::Char('l') => motion_handler::l(app),
+        KeyCode::Char('0') => app.cursor.reset_x(),
+        KeyCode::Char('$') => motion_handler::dollar_sign(app),
+        KeyCode::Char('G') => motion_handler::G(app),
+        KeyCode::Char('H') => motion_handler::H(app),
+        KeyCode::Char('M') => motion_handler::M(app),
+        KeyCode::Char('L') => motion_handler::L(app),
+        KeyCode::Char('w') => motion_handler::w(app),
+        KeyCode::Char('b') => motion_handler::b(app),
+        KeyCode::Char('e') => motion_handler::e(app),
         _ => {}
     };
 
     Ok(())
 }
 
-fn handle_visual_line_mode(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
-    match key_event.code {
-        KeyCode::Esc => {
-            app.cursor.left();
-            let _ = app.set_mode(Mode::Normal)?;
-        }
-        _ => {}
-    };
+fn handle_visual_mode(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
+    handle_visual_motion(key_event, app)
+}
 
-    Ok(())
+fn handle_visual_block_mode(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
+    handle_visual_motion(key_event, app)
+}
+
+fn handle_visual_line_mode(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
+    handle_visual_motion(key_event, app)
 }
 
 fn handle_pending_mode(key_event: KeyEvent, app: &mut App) -> AppResult<()> {
@@ -281,26 +628,40 @@ fn handle_compound_inputs(
 
     let _ = app.set_mode(Mode::Pending)?;
 
-    let captured_buffer_content = app.buffer_content.clone();
-
     app.command_buffer.add(&key_event.code.to_string());
 
     if app.command_buffer.valid().unwrap_or(false) {
         let command = app.command_buffer.buffer.clone();
 
+        // macros feed keys through the handler again, so the buffer has to be
+        // empty before anything runs
+        app.command_buffer.clear();
+
         // set mode to normal BEFORE motion executes as it might change the mode
         // this is just to ensure we don't stay in pending mode
         let _ = app.set_mode(Mode::Normal)?;
 
         // Line 1 has '../' and is immutable
-        if app.cursor.y == 1 {
-            app.command_buffer.clear();
+        let operator = command.trim_start_matches(|c: char| c.is_ascii_digit());
+        if app.cursor.y == 1 && operator.starts_with(['c', 'd']) {
             return Ok(());
         }
 
         // TODO consider moving this into a separate "execute" call
         match command.as_str() {
             "gg" => app.cursor.reset_y(),
+            "gl" => open_hovered(app, false),
+            "gt" => app.next_tab(true),
+            "gT" => app.next_tab(false),
+            "zt" => motion_handler::zt(app),
+            "zz" => motion_handler::zz(app),
+            "zb" => motion_handler::zb(app),
+            "zo" => fold(app, tree::expand),
+            "zc" => fold(app, tree::collapse),
+            "za" => fold(app, tree::toggle),
+            "gy" => motion_handler::gy(app),
+            "g-" => app.undo_earlier(),
+            "g+" => app.undo_later(),
             "cj" => motion_handler::cj(app),
             "ck" => motion_handler::ck(app),
             "dj" => motion_handler::dj(app),
@@ -313,84 +674,190 @@ fn handle_compound_inputs(
             "yiw" => motion_handler::yiw(app),
             "ciw" => motion_handler::ciw(app),
             "diw" => motion_handler::diw(app),
-            _ => {}
+            _ if command.starts_with('"') => {
+                app.yank_buffer
+                    .select(command.chars().nth(1).unwrap_or('"'));
+            }
+            _ if command.starts_with('m') => {
+                if let Err(error) = app.set_mark(command.chars().nth(1).unwrap_or('m')) {
+                    app.echo_error(&error);
+                }
+            }
+            _ if command.starts_with(['\'', '`']) => {
+                if let Err(error) = app.jump_to_mark(command.chars().nth(1).unwrap_or('\'')) {
+                    app.echo_error(&error);
+                }
+            }
+            _ if command.starts_with('q') => {
+                app.macros.start(command.chars().nth(1).unwrap_or('q'));
+            }
+            _ if command.contains('@') => play_macro(app, &command)?,
+            _ => {
+                if !motion_handler::word_command(app, &command) {
+                    motion_handler::text_object(app, &command);
+                }
+            }
         }
 
         app.command_buffer.clear();
     }
 
-    if app.buffer_content != captured_buffer_content {
-        app.undo_stack.push(
-            captured_buffer_content,
-            app.cursor.x.into(),
-            app.cursor.y.into(),
-        );
-    }
-
     Ok(())
 }
 
+fn play_macro(app: &mut App, command: &str) -> AppResult<()> {
+    const MAX_DEPTH: usize = 100;
+
+    let (count, register) = command.split_once('@').unwrap_or(("", "@"));
+    let count = count.parse::<usize>().unwrap_or(1);
+    let register = register.chars().next().unwrap_or('@');
+
+    let keys = match app.macros.get(register) {
+        Some(keys) if app.macros.playing < MAX_DEPTH => keys,
+        _ => return Ok(()),
+    };
+
+    app.macros.playing += 1;
+    let result = (0..count).try_for_each(|_| {
+        keys.iter()
+            .try_for_each(|key_event| handle_key_events(*key_event, app))
+    });
+    app.macros.playing -= 1;
+
+    result
+}
+
+// where a path ends up once the directories it is in have been renamed or
+// moved, directory paths end in a slash
+fn relocate(path: &str, relocated: &[(String, String)]) -> String {
+    relocated.iter().fold(path.to_string(), |path, (from, to)| {
+        match path.strip_prefix(from.as_str()) {
+            Some(rest) => format!("{}{}", to, rest),
+            None => path,
+        }
+    })
+}
+
 pub fn handle_confirm(key_event: &KeyEvent, app: &mut App) -> AppResult<()> {
     match key_event.code {
         KeyCode::Char('y') => {
             let files_to_delete = app.get_files(State::Deleted);
             let files_to_rename = app.get_files(State::Modified);
-            let files_to_create = app.get_files(State::Created);
-            let files_to_move = app.get_files(State::Created);
-
+            let (files_to_copy, files_to_create): (Vec<_>, Vec<_>) = app
+                .get_files(State::Created)
+                .into_iter()
+                .partition(|file| file.source.is_some());
+            let files_to_move = app.get_files(State::Moved);
+
+            // a file that was pasted into another directory is deleted from
+            // its own by moving it, one that was copied only once the copy
+            // has been made
+            let moved_away: Vec<String> = files_to_move
+                .iter()
+                .chain(files_to_rename.iter())
+                .map(|file| file.original_path())
+                .collect();
+            let copied: Vec<String> = files_to_copy
+                .iter()
+                .filter_map(|file| file.source.clone())
+                .collect();
+            let (copy_sources, files_to_delete): (Vec<_>, Vec<_>) = files_to_delete
+                .into_iter()
+                .filter(|file| !moved_away.contains(&file.original_path()))
+                .partition(|file| copied.contains(&file.original_path()));
+
+            // every operation is tried, the ones that failed are reported
+            // once the listing shows what is on disk
+            let mut failures: Vec<String> = vec![];
             for file in files_to_delete {
-                let _ = system::delete_file(condense_path_name(file))?;
+                if let Err(error) = system::delete_file(file.original_path()) {
+                    failures.push(format!("Cannot delete {}: {}", file.original_path(), error));
+                }
             }
-            for file in files_to_rename {
-                let new_file_name = file.name.clone().trim().to_string();
-                let _ = system::rename_file(file.original_name.clone(), new_file_name)?;
+
+            // a directory that was renamed or moved takes the changes still to
+            // come inside of it along
+            let mut relocated: Vec<(String, String)> = vec![];
+            let mut renamed: Vec<(String, String)> = vec![];
+            for file in files_to_rename.iter().chain(files_to_move.iter()) {
+                let from = relocate(&file.original_path(), &relocated);
+                let to = relocate(&file.path(), &relocated);
+                if let Err(error) = system::move_file(from.clone(), to.clone()) {
+                    failures.push(format!("Cannot move {} to {}: {}", from, to, error));
+                    continue;
+                }
+
+                renamed.push((file.original_path(), to.clone()));
+                if file.file_type == FileType::Directory {
+                    relocated.push((from, to));
+                }
             }
             for file in files_to_create {
-                let new_file_name = file.name.clone().trim().to_string();
-                let _ = system::create_file(new_file_name)?;
+                let path = relocate(&file.path(), &relocated);
+                if let Err(error) = system::create_file(path.clone()) {
+                    failures.push(format!("Cannot create {}: {}", path, error));
+                }
             }
-            for file in files_to_move {
-                let new_file_name = file.name.clone().trim().to_string();
-                let _ = system::move_file(file.original_name.clone(), new_file_name)?;
+
+            // copies go where the renames put their source and their target,
+            // a source stays where it is when one of its copies failed
+            let mut failed_sources: Vec<String> = vec![];
+            for file in files_to_copy {
+                let source = file.source.clone().unwrap_or_default();
+                let from = renamed
+                    .iter()
+                    .find(|(from, _)| from.trim_end_matches('/') == source.trim_end_matches('/'))
+                    .map(|(_, to)| to.clone())
+                    .unwrap_or_else(|| relocate(&source, &relocated));
+                if let Err(error) = system::copy_file(from, relocate(&file.path(), &relocated)) {
+                    failures.push(format!("Cannot copy {}: {}", source, error));
+                    failed_sources.push(source);
+                }
+            }
+            for file in copy_sources {
+                let source = file.original_path();
+                if failed_sources.contains(&source) {
+                    continue;
+                }
+                if let Err(error) = system::delete_file(relocate(&source, &relocated)) {
+                    failures.push(format!("Cannot delete {}: {}", source, error));
+                }
             }
 
-            app.command = None;
-            let _ = app.set_mode(Mode::Normal)?;
+            // everything pending is on disk now or failed, the views start over
             app.need_confirmation = false;
+            app.reload();
+
+            app.command_line.close();
+            let _ = app.set_mode(Mode::Normal)?;
+
+            // a failure keeps the app open so the error can be read
+            if let Some(failure) = failures.first() {
+                app.quit_after_save = false;
+                app.echo_error(&match failures.len() {
+                    1 => failure.clone(),
+                    count => format!("{} (and {} more)", failure, count - 1),
+                });
+            }
+
+            if app.quit_after_save {
+                app.quit();
+            }
         }
         KeyCode::Char('n') => {
-            app.command = None;
+            app.command_line.close();
             let _ = app.set_mode(Mode::Normal)?;
             app.need_confirmation = false;
+            app.quit_after_save = false;
         }
         KeyCode::Esc => {
-            app.command = None;
-            app.need_confirmation = false;
+            app.command_line.close();
             let _ = app.set_mode(Mode::Normal)?;
             app.need_confirmation = false;
+            app.quit_after_save = false;
         }
         _ => {}
     }
 
     Ok(())
 }
-
-fn condense_path_name(file: FileEntry) -> String {
-    let pwd = system::pwd();
-    let path = file.dir.clone();
-    let path = path.trim_start_matches(pwd.as_str());
-
-    let path = path.trim_start_matches("./");
-    let path = path.trim_start_matches("../");
-
-    let mut name = match path.ends_with("/") {
-        true => format!("{}{}", path, file.name),
-        false => format!("{}/{}", path, file.name),
-    };
-
-    if file.file_type == FileType::Directory {
-        name = name + "/";
-    }
-
-    name
-}
@@ -44,8 +44,25 @@ async fn main() -> AppResult<()> {
             Event::Mouse(_) => {}
             Event::Resize(_, _) => {}
         }
+
+        // the editor gets the terminal until it exits
+        if let Some(path) = app.pending_open.take() {
+            tui.suspend()?;
+            let result = system::open_in_editor(&path, &app.path.get_absolute_path());
+            tui.resume()?;
+
+            let _ = crossterm::execute!(std::io::stdout(), SetCursorStyle::SteadyBlock);
+
+            match result {
+                Ok(Some(0)) => {}
+                Ok(Some(code)) => app.echo_error(&format!("Editor returned {}", code)),
+                Ok(None) => app.echo_error("Editor was terminated"),
+                Err(error) => app.echo_error(&format!("Cannot start editor: {}", error)),
+            }
+        }
     }
 
+    let _ = app.buffer_storage.frecency.flush();
     tui.exit()?;
 
     Ok(())
@@ -65,6 +65,29 @@ impl<B: Backend> Tui<B> {
         Ok(())
     }
 
+    /// Hands the terminal over to another program, like an editor.
+    ///
+    /// Events stop being read until [`resume`] is called.
+    ///
+    /// [`resume`]: Tui::resume
+    pub fn suspend(&mut self) -> AppResult<()> {
+        self.events.stop();
+        Self::reset()?;
+        self.terminal.show_cursor()?;
+        Ok(())
+    }
+
+    /// Takes the terminal back after [`suspend`].
+    ///
+    /// [`suspend`]: Tui::suspend
+    pub fn resume(&mut self) -> AppResult<()> {
+        terminal::enable_raw_mode()?;
+        crossterm::execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
+        self.events = self.events.restart();
+        self.terminal.clear()?;
+        Ok(())
+    }
+
     /// Exits the terminal interface.
     ///
     /// It disables the raw mode and reverts back the terminal properties.
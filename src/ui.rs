@@ -1,4 +1,4 @@
-use crate::app::App;
+use crate::app::{App, Mode};
 use crate::utils::render_utils;
 use ratatui::{
     layout::{Constraint, Direction, Layout},
@@ -41,16 +41,51 @@ pub fn render(app: &mut App, frame: &mut Frame) {
         ])
         .split(chunks[1]);
 
+    // in dual-pane mode a second listing takes the place of the parent and
+    // the preview pane
+    let (current_area, other_area) = match &app.dual_pane {
+        Some(pane) => {
+            let halves = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)])
+                .split(chunks[1]);
+
+            match pane.focus_left {
+                true => (halves[0], Some(halves[1])),
+                false => (halves[1], Some(halves[0])),
+            }
+        }
+        None => (body_chunks[1], None),
+    };
+
+    // the listing scrolls with the cursor, so the view has to be known first
+    app.cursor.container = Some(current_area);
+    app.cursor.height = current_area.height.saturating_sub(2);
+
+    let title = app.path.get_absolute_path();
+    app.refresh_other_pane();
     let body = render_utils::get_body(app);
-    frame.render_widget(body.parent, body_chunks[0]);
-    frame.render_widget(body.current, body_chunks[1]);
-    frame.render_widget(body.child, body_chunks[2]);
+    match other_area {
+        Some(_) => frame.render_widget(
+            body.current
+                .block(render_utils::get_pane_block(title, true)),
+            current_area,
+        ),
+        None => {
+            frame.render_widget(body.parent, body_chunks[0]);
+            frame.render_widget(body.current, body_chunks[1]);
+            frame.render_widget(body.child, body_chunks[2]);
+        }
+    }
 
-    app.cursor.container = Some(body_chunks[1]);
+    if let (Some(area), Some(pane)) = (other_area, &app.dual_pane) {
+        let other = render_utils::get_other_pane(app, &pane.other, area.height.saturating_sub(2));
+        frame.render_widget(other, area);
+    }
 
     let position = Position {
-        x: body_chunks[1].x + app.cursor.x,
-        y: body_chunks[1].y + app.cursor.y,
+        x: current_area.x + app.cursor.x,
+        y: current_area.y + app.cursor.y - app.cursor.scroll,
     };
     frame.set_cursor_position(position);
 
@@ -68,5 +103,58 @@ pub fn render(app: &mut App, frame: &mut Frame) {
         frame.render_widget(content, area);
     }
 
+    if let Some(popup) = &app.popup {
+        let area = render_utils::popup_area(frame.area(), 60, 40);
+
+        let block = Block::bordered()
+            .title(Line::from(popup.title.clone()).centered())
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded);
+
+        let content = render_utils::get_popup_content(&block, popup, area.height);
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(content, area);
+    }
+
+    if let Some(finder) = &app.finder {
+        let area = render_utils::popup_area(frame.area(), 60, 60);
+
+        let block = Block::bordered()
+            .title(Line::from(" Find ").centered())
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded);
+
+        let content = render_utils::get_finder_content(&block, finder, area.height);
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(content, area);
+    }
+
     app.cursor.update_frame(frame);
+
+    // while typing a command or a search the cursor belongs to the line in
+    // the footer, past the border and the prompt
+    let line_cursor = match app.mode {
+        Mode::Command => Some(app.command_line.cursor),
+        Mode::Search => Some(app.search.line.cursor),
+        _ => None,
+    };
+
+    if let Some(cursor) = line_cursor {
+        frame.set_cursor_position(Position {
+            x: chunks[2].x + 2 + cursor as u16,
+            y: chunks[2].y + 1,
+        });
+    }
+
+    // the finder's query sits on the first line inside its border, after "> "
+    if let Some(finder) = &app.finder {
+        let area = render_utils::popup_area(frame.area(), 60, 60);
+
+        frame.set_cursor_position(Position {
+            x: area.x + 3 + finder.line.cursor as u16,
+            y: area.y + 1,
+        });
+    }
 }
@@ -1,6 +1,7 @@
-use crate::utils::system;
+use crate::utils::{frecency::Frecency, system};
 use std::collections::HashMap;
 use std::fs;
+use std::path::Path;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum State {
@@ -35,7 +36,17 @@ pub enum FileType {
     Unknown,
 }
 
-#[derive(Debug, Clone)]
+impl FileType {
+    // a name typed into the listing is a directory when it ends in a slash
+    pub fn from_name(name: &str) -> Self {
+        match name.ends_with('/') {
+            true => FileType::Directory,
+            false => FileType::File,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct FileEntry {
     pub original_name: String,
     pub name: String,
@@ -43,9 +54,33 @@ pub struct FileEntry {
     pub dir: String,
     pub state: State,
     pub file_type: FileType,
+    // a created entry with a source is a copy of it
+    pub source: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+impl FileEntry {
+    // where the file is now and where it will be once the changes are
+    // applied, directories end in a slash
+    pub fn original_path(&self) -> String {
+        Self::join(&self.original_dir, &self.original_name, &self.file_type)
+    }
+
+    pub fn path(&self) -> String {
+        Self::join(&self.dir, self.name.trim(), &self.file_type)
+    }
+
+    fn join(dir: &str, name: &str, file_type: &FileType) -> String {
+        let mut path = format!("{}/{}", dir.trim_end_matches('/'), name);
+
+        if *file_type == FileType::Directory && !path.ends_with('/') {
+            path.push('/');
+        }
+
+        path
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct DirBuffer {
     pub dir: String,
     pub files: HashMap<String, FileEntry>,
@@ -81,6 +116,7 @@ impl DirBuffer {
                         original_dir: dir.clone(),
                         state: State::Unmodified,
                         file_type: get_file_type(&path.to_string_lossy()),
+                        source: None,
                     },
                 );
             }
@@ -110,6 +146,7 @@ impl DirBuffer {
                         original_dir: String::new(),
                         state: State::Unmodified,
                         file_type: get_file_type(trimmed),
+                        source: None,
                     },
                 );
             }
@@ -144,15 +181,25 @@ impl DirBuffer {
             file.name = new_name.to_string();
 
             if file.state == State::Created {
+                // adding or removing the trailing slash of a new entry
+                // turns it into a directory or back into a file
+                if file.source.is_none() && name.ends_with('/') != new_name.ends_with('/') {
+                    file.file_type = FileType::from_name(new_name);
+                }
+
                 self.files.insert(new_name.to_string(), file);
                 return;
             }
 
-            if file.original_name == name {
-                file.state = State::Unmodified;
-            } else {
-                file.state = State::Modified;
-            }
+            // an entry pasted from another directory is still moved
+            file.state = match (
+                file.original_name == new_name,
+                file.original_dir == file.dir,
+            ) {
+                (true, true) => State::Unmodified,
+                (true, false) => State::Moved,
+                (false, _) => State::Modified,
+            };
 
             self.files.insert(new_name.to_string(), file);
         }
@@ -193,10 +240,44 @@ impl DirBuffer {
             dir: self.dir.clone(),
             state: State::Created,
             file_type,
+            source: None,
         };
         self.files.insert(name.to_string(), file);
     }
 
+    // an entry pasted from another directory, it is moved here when `moved`
+    // is set and copied otherwise. Files that only exist as pending changes
+    // are created here instead
+    pub fn add_entry(&mut self, file: &FileEntry, moved: bool) {
+        let on_disk = Path::new(&file.original_path()).exists();
+
+        let entry = match moved && on_disk && file.source.is_none() {
+            true => FileEntry {
+                original_name: file.original_name.clone(),
+                name: file.name.clone(),
+                original_dir: file.original_dir.clone(),
+                dir: self.dir.clone(),
+                state: State::Moved,
+                file_type: file.file_type.clone(),
+                source: None,
+            },
+            false => FileEntry {
+                original_name: file.name.clone(),
+                name: file.name.clone(),
+                original_dir: self.dir.clone(),
+                dir: self.dir.clone(),
+                state: State::Created,
+                file_type: file.file_type.clone(),
+                source: file
+                    .source
+                    .clone()
+                    .or_else(|| on_disk.then(|| file.original_path())),
+            },
+        };
+
+        self.files.insert(file.name.clone(), entry);
+    }
+
     pub fn set_dir(&mut self, name: &str, dir: &str) {
         if let Some(file) = self.files.get_mut(name) {
             file.dir = dir.to_string();
@@ -223,26 +304,73 @@ impl DirBuffer {
     pub fn get_file(&self, name: &str) -> Option<FileEntry> {
         self.files.get(name).cloned()
     }
+
+    // what the directory holds with the pending changes made to it,
+    // directories first like the listing
+    pub fn listed_names(&self) -> Vec<String> {
+        let mut names: Vec<(bool, String)> = self
+            .files
+            .values()
+            .filter(|file| file.state != State::Deleted)
+            .map(|file| {
+                (
+                    file.file_type != FileType::Directory,
+                    file.name.trim().to_string(),
+                )
+            })
+            .collect();
+
+        names.sort();
+        names.into_iter().map(|(_, name)| name).collect()
+    }
+
+    pub fn has_changes(&self) -> bool {
+        self.files
+            .values()
+            .any(|file| file.state != State::Unmodified)
+    }
 }
 
 #[derive(Debug)]
 pub struct BufferStorage {
     pub views: HashMap<String, DirBuffer>,
+    // what the views changed since the last `take_touched` looked like
+    // before, so a change doesn't need a copy of every view up front
+    touched: HashMap<String, DirBuffer>,
+    // every directory that gets a view counts as a visit
+    pub frecency: Frecency,
 }
 
 impl BufferStorage {
     pub fn new() -> Self {
         BufferStorage {
             views: HashMap::new(),
+            touched: HashMap::new(),
+            frecency: Frecency::new(),
         }
     }
 
+    fn touch(&mut self, dir: &str) {
+        if self.touched.contains_key(dir) {
+            return;
+        }
+
+        if let Some(view) = self.views.get(dir) {
+            self.touched.insert(dir.to_string(), view.clone());
+        }
+    }
+
+    pub fn take_touched(&mut self) -> HashMap<String, DirBuffer> {
+        std::mem::take(&mut self.touched)
+    }
+
     pub fn add_view(&mut self, dir: String) -> Result<(), std::io::Error> {
         if let Some(_) = self.get_view(&dir) {
             return Ok(());
         }
 
         let buffer = DirBuffer::new(&dir)?;
+        self.frecency.visit(&dir);
         self.views.insert(dir, buffer);
 
         Ok(())
@@ -257,19 +385,17 @@ impl BufferStorage {
         self.views.get(dir).cloned()
     }
 
+    pub fn get_view_mut(&mut self, dir: &str) -> Option<&mut DirBuffer> {
+        self.touch(dir);
+        self.views.get_mut(dir)
+    }
+
     pub fn update_view(&mut self, dir: &str, buffer: DirBuffer) {
+        self.touch(dir);
         self.views.insert(dir.to_string(), buffer);
     }
 
     pub fn has_changes(&self) -> bool {
-        for buffer in self.views.values() {
-            for file in buffer.files.values() {
-                if file.state != State::Unmodified {
-                    return true;
-                }
-            }
-        }
-
-        false
+        self.views.values().any(|buffer| buffer.has_changes())
     }
 }
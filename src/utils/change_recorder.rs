@@ -0,0 +1,59 @@
+use crossterm::event::KeyEvent;
+
+// Records the keys of the change currently being typed so `.` can feed them
+// through the handler again. A sequence starts and ends in idle normal mode.
+#[derive(Debug, Default)]
+pub struct ChangeRecorder {
+    keys: Vec<KeyEvent>,
+    start_content: Option<String>,
+    last_change: Vec<KeyEvent>,
+    pub replaying: bool,
+}
+
+impl ChangeRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn begin(&mut self, buffer_content: &str) {
+        if self.replaying {
+            return;
+        }
+
+        self.keys.clear();
+        self.start_content = Some(buffer_content.to_string());
+    }
+
+    pub fn record(&mut self, key_event: KeyEvent) {
+        if self.replaying || self.start_content.is_none() {
+            return;
+        }
+
+        self.keys.push(key_event);
+    }
+
+    // keeps the sequence only if it actually changed the buffer, motions and
+    // yanks don't overwrite the last change
+    pub fn finish(&mut self, buffer_content: &str) {
+        if self.replaying {
+            return;
+        }
+
+        if let Some(start_content) = self.start_content.take() {
+            if start_content != buffer_content && !self.keys.is_empty() {
+                self.last_change = std::mem::take(&mut self.keys);
+            }
+        }
+
+        self.keys.clear();
+    }
+
+    pub fn discard(&mut self) {
+        self.start_content = None;
+        self.keys.clear();
+    }
+
+    pub fn last_change(&self) -> Vec<KeyEvent> {
+        self.last_change.clone()
+    }
+}
@@ -0,0 +1,74 @@
+use std::env;
+use std::io::Write;
+
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64(input: &[u8]) -> String {
+    let mut output = String::with_capacity(input.len().div_ceil(3) * 4);
+
+    for chunk in input.chunks(3) {
+        let bytes = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let triple = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+
+        for index in 0..4 {
+            if index <= chunk.len() {
+                let sextet = (triple >> (18 - index * 6)) & 0b111111;
+                output.push(BASE64_ALPHABET[sextet as usize] as char);
+            } else {
+                output.push('=');
+            }
+        }
+    }
+
+    output
+}
+
+// tmux and screen swallow unknown escape sequences unless they are wrapped
+// in a passthrough DCS
+fn wrap_for_multiplexer(sequence: String) -> String {
+    if env::var("TMUX").is_ok() {
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else if env::var("TERM").unwrap_or_default().starts_with("screen") {
+        format!("\x1bP{}\x1b\\", sequence)
+    } else {
+        sequence
+    }
+}
+
+// Sets the terminal's clipboard through OSC 52, this works over ssh as long as
+// the terminal emulator supports it and doesn't need any external tool
+pub fn copy(content: &str) -> Result<(), std::io::Error> {
+    let sequence = format!("\x1b]52;c;{}\x07", base64(content.as_bytes()));
+
+    let mut stdout = std::io::stdout();
+    stdout.write_all(wrap_for_multiplexer(sequence).as_bytes())?;
+    stdout.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the test vectors of RFC 4648
+    #[test]
+    fn pads_to_a_multiple_of_four() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foob"), "Zm9vYg==");
+        assert_eq!(base64(b"fooba"), "Zm9vYmE=");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn encodes_every_bit() {
+        assert_eq!(base64(&[0xff, 0xfe, 0x00]), "//4A");
+        assert_eq!(base64("/tmp/é".as_bytes()), "L3RtcC/DqQ==");
+    }
+}
@@ -0,0 +1,398 @@
+use crate::utils::system;
+
+const HISTORY_SIZE: usize = 100;
+
+#[derive(Debug, Clone)]
+struct Completion {
+    // char index where the completed word starts
+    start: usize,
+    original: String,
+    candidates: Vec<String>,
+    index: Option<usize>,
+}
+
+// The line typed after `:`, with a cursor, history that is kept in the data
+// directory between runs and Tab completion. The cursor is a char index.
+#[derive(Debug, Clone)]
+pub struct CommandLine {
+    pub content: String,
+    pub cursor: usize,
+    history: Vec<String>,
+    history_file: &'static str,
+    history_index: Option<usize>,
+    // what was typed before walking through the history, it also filters it
+    draft: String,
+    completion: Option<Completion>,
+}
+
+fn byte_index(content: &str, index: usize) -> usize {
+    content
+        .char_indices()
+        .nth(index)
+        .map(|(byte, _)| byte)
+        .unwrap_or(content.len())
+}
+
+impl CommandLine {
+    pub fn new(history_file: &'static str) -> Self {
+        let history = system::read_data_file(history_file)
+            .unwrap_or_default()
+            .lines()
+            .map(String::from)
+            .collect();
+
+        Self {
+            content: String::new(),
+            cursor: 0,
+            history,
+            history_file,
+            history_index: None,
+            draft: String::new(),
+            completion: None,
+        }
+    }
+
+    pub fn open(&mut self, content: &str) {
+        self.content = content.to_string();
+        self.cursor = content.chars().count();
+        self.history_index = None;
+        self.completion = None;
+    }
+
+    pub fn close(&mut self) {
+        self.open("");
+    }
+
+    // empties the line and keeps what was on it in the history
+    pub fn take(&mut self) -> String {
+        let content = std::mem::take(&mut self.content);
+        self.close();
+
+        if !content.trim().is_empty() {
+            self.history.retain(|entry| *entry != content);
+            self.history.push(content.clone());
+
+            let overflow = self.history.len().saturating_sub(HISTORY_SIZE);
+            self.history.drain(..overflow);
+
+            let _ = system::write_data_file(self.history_file, &self.history.join("\n"));
+        }
+
+        content
+    }
+
+    fn edited(&mut self) {
+        self.history_index = None;
+        self.completion = None;
+    }
+
+    fn length(&self) -> usize {
+        self.content.chars().count()
+    }
+
+    pub fn insert(&mut self, c: char) {
+        let index = byte_index(&self.content, self.cursor);
+        self.content.insert(index, c);
+        self.cursor += 1;
+        self.edited();
+    }
+
+    // removes chars start..end (char indices) and puts the cursor at start
+    fn remove(&mut self, start: usize, end: usize) {
+        let (from, to) = (
+            byte_index(&self.content, start),
+            byte_index(&self.content, end),
+        );
+
+        self.content.replace_range(from..to, "");
+        self.cursor = start;
+        self.edited();
+    }
+
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.remove(self.cursor - 1, self.cursor);
+        }
+    }
+
+    pub fn delete(&mut self) {
+        if self.cursor < self.length() {
+            self.remove(self.cursor, self.cursor + 1);
+        }
+    }
+
+    // Ctrl-w: the word or run of other chars before the cursor and the
+    // spaces after it
+    pub fn delete_word(&mut self) {
+        let chars: Vec<char> = self.content.chars().take(self.cursor).collect();
+        let mut start = chars.len();
+
+        while start > 0 && chars[start - 1].is_whitespace() {
+            start -= 1;
+        }
+
+        let is_word = |c: char| c.is_alphanumeric() || c == '_';
+        match start.checked_sub(1).map(|index| chars[index]) {
+            Some(c) if is_word(c) => {
+                while start > 0 && is_word(chars[start - 1]) {
+                    start -= 1;
+                }
+            }
+            Some(_) => {
+                while start > 0 && !is_word(chars[start - 1]) && !chars[start - 1].is_whitespace() {
+                    start -= 1;
+                }
+            }
+            None => {}
+        }
+
+        self.remove(start, self.cursor);
+    }
+
+    // Ctrl-u: everything before the cursor
+    pub fn delete_to_start(&mut self) {
+        self.remove(0, self.cursor);
+    }
+
+    pub fn left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.length());
+    }
+
+    pub fn home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn end(&mut self) {
+        self.cursor = self.length();
+    }
+
+    pub fn before_cursor(&self) -> &str {
+        &self.content[..byte_index(&self.content, self.cursor)]
+    }
+
+    fn show(&mut self, content: String) {
+        self.content = content;
+        self.cursor = self.length();
+        self.completion = None;
+    }
+
+    // like vim, only entries starting with what was typed are recalled
+    pub fn history_older(&mut self) {
+        if self.history_index.is_none() {
+            self.draft = self.content.clone();
+        }
+
+        let end = self.history_index.unwrap_or(self.history.len());
+        let found = self.history[..end]
+            .iter()
+            .rposition(|entry| entry.starts_with(&self.draft));
+
+        if let Some(index) = found {
+            self.show(self.history[index].clone());
+            self.history_index = Some(index);
+        }
+    }
+
+    pub fn history_newer(&mut self) {
+        let start = match self.history_index {
+            Some(index) => index + 1,
+            None => return,
+        };
+
+        let found = self.history[start..]
+            .iter()
+            .position(|entry| entry.starts_with(&self.draft))
+            .map(|offset| start + offset);
+
+        match found {
+            Some(index) => {
+                self.show(self.history[index].clone());
+                self.history_index = Some(index);
+            }
+            None => {
+                self.show(self.draft.clone());
+                self.history_index = None;
+            }
+        }
+    }
+
+    pub fn is_completing(&self) -> bool {
+        self.completion.is_some()
+    }
+
+    // Tab and Shift-Tab. The first press replaces the word that starts at
+    // the given char index with the first candidate, the following ones
+    // cycle through them and back to what was typed
+    pub fn complete(&mut self, forward: bool, (start, candidates): (usize, Vec<String>)) {
+        let mut completion = match self.completion.take() {
+            Some(completion) => completion,
+            None if candidates.is_empty() => return,
+            None => Completion {
+                start,
+                original: self
+                    .content
+                    .chars()
+                    .skip(start)
+                    .take(self.cursor - start)
+                    .collect(),
+                candidates,
+                index: None,
+            },
+        };
+
+        let count = completion.candidates.len();
+        completion.index = match (completion.index, forward) {
+            (None, true) => Some(0),
+            (None, false) => Some(count - 1),
+            (Some(index), true) if index + 1 < count => Some(index + 1),
+            (Some(index), false) if index > 0 => Some(index - 1),
+            _ => None,
+        };
+
+        let replacement = match completion.index {
+            Some(index) => completion.candidates[index].clone(),
+            None => completion.original.clone(),
+        };
+
+        let start = completion.start;
+        let (from, to) = (
+            byte_index(&self.content, start),
+            byte_index(&self.content, self.cursor),
+        );
+        self.content.replace_range(from..to, &replacement);
+        self.cursor = start + replacement.chars().count();
+
+        // a single match is final, the next Tab completes from there
+        if count > 1 {
+            self.completion = Some(completion);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(history: &[&str], content: &str) -> CommandLine {
+        CommandLine {
+            content: content.to_string(),
+            cursor: content.chars().count(),
+            history: history.iter().map(|entry| entry.to_string()).collect(),
+            history_file: "test_history",
+            history_index: None,
+            draft: String::new(),
+            completion: None,
+        }
+    }
+
+    fn candidates(start: usize, names: &[&str]) -> (usize, Vec<String>) {
+        (start, names.iter().map(|name| name.to_string()).collect())
+    }
+
+    #[test]
+    fn history_only_recalls_entries_starting_with_the_typed_text() {
+        let mut line = line(&["set tree", "echo", "set list", "w"], "se");
+
+        line.history_older();
+        assert_eq!(line.content, "set list");
+        line.history_older();
+        assert_eq!(line.content, "set tree");
+        line.history_older();
+        assert_eq!(line.content, "set tree");
+
+        line.history_newer();
+        assert_eq!(line.content, "set list");
+        line.history_newer();
+        assert_eq!(line.content, "se");
+        assert_eq!(line.cursor, 2);
+    }
+
+    #[test]
+    fn empty_line_recalls_the_whole_history() {
+        let mut line = line(&["one", "two"], "");
+
+        line.history_older();
+        assert_eq!(line.content, "two");
+        line.history_older();
+        assert_eq!(line.content, "one");
+    }
+
+    #[test]
+    fn ctrl_w_deletes_a_word_or_a_run_of_other_chars() {
+        let mut line = line(&[], "cd ../foo_bar  ");
+
+        line.delete_word();
+        assert_eq!(line.content, "cd ../");
+        line.delete_word();
+        assert_eq!(line.content, "cd ");
+        line.delete_word();
+        assert_eq!(line.content, "");
+        line.delete_word();
+        assert_eq!(line.content, "");
+    }
+
+    #[test]
+    fn ctrl_w_stops_at_the_cursor() {
+        let mut line = line(&[], "norm dd");
+        line.cursor = 4;
+
+        line.delete_word();
+        assert_eq!(line.content, " dd");
+        assert_eq!(line.cursor, 0);
+    }
+
+    #[test]
+    fn tab_cycles_through_the_candidates_and_back_to_the_typed_text() {
+        let mut line = line(&[], "set tr");
+
+        line.complete(true, candidates(4, &["tree", "trim"]));
+        assert_eq!(line.content, "set tree");
+        line.complete(true, candidates(4, &[]));
+        assert_eq!(line.content, "set trim");
+        line.complete(true, candidates(4, &[]));
+        assert_eq!(line.content, "set tr");
+        assert_eq!(line.cursor, 6);
+        line.complete(true, candidates(4, &[]));
+        assert_eq!(line.content, "set tree");
+    }
+
+    #[test]
+    fn shift_tab_starts_from_the_last_candidate() {
+        let mut line = line(&[], "set tr");
+
+        line.complete(false, candidates(4, &["tree", "trim"]));
+        assert_eq!(line.content, "set trim");
+        line.complete(false, candidates(4, &[]));
+        assert_eq!(line.content, "set tree");
+        line.complete(false, candidates(4, &[]));
+        assert_eq!(line.content, "set tr");
+    }
+
+    #[test]
+    fn a_single_candidate_is_final() {
+        let mut line = line(&[], "set tr");
+
+        line.complete(true, candidates(4, &["tree"]));
+        assert_eq!(line.content, "set tree");
+        assert!(!line.is_completing());
+
+        line.complete(true, candidates(4, &["tree", "treeview"]));
+        assert_eq!(line.content, "set tree");
+        assert!(line.is_completing());
+    }
+
+    #[test]
+    fn editing_counts_chars() {
+        let mut line = line(&[], "é");
+
+        line.insert('x');
+        line.left();
+        line.backspace();
+        assert_eq!(line.content, "x");
+        assert_eq!(line.cursor, 0);
+    }
+}
@@ -0,0 +1,63 @@
+use crate::app::App;
+use crate::utils::ex_command::ExCommand;
+
+pub type CommandHandler = fn(&mut App, &ExCommand) -> Result<(), String>;
+
+#[derive(Debug, Clone)]
+pub struct CommandSpec {
+    pub name: String,
+    // how much of the name has to be typed, `reg` for `registers`
+    pub abbreviation: usize,
+    pub handler: CommandHandler,
+}
+
+// Every Ex command lives here, subsystems add theirs with `register` using
+// vim's notation for abbreviations: "reg[isters]" accepts `reg` up to
+// `registers`.
+#[derive(Debug, Default)]
+pub struct CommandRegistry {
+    commands: Vec<CommandSpec>,
+}
+
+impl CommandRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&mut self, notation: &str, handler: CommandHandler) {
+        let abbreviation = notation.find('[').unwrap_or(notation.len());
+        let name: String = notation
+            .chars()
+            .filter(|c| *c != '[' && *c != ']')
+            .collect();
+
+        self.commands.retain(|command| command.name != name);
+        self.commands.push(CommandSpec {
+            name,
+            abbreviation,
+            handler,
+        });
+    }
+
+    pub fn find(&self, name: &str) -> Option<&CommandSpec> {
+        self.commands
+            .iter()
+            .find(|command| command.name == name)
+            .or_else(|| {
+                self.commands.iter().find(|command| {
+                    name.len() >= command.abbreviation && command.name.starts_with(name)
+                })
+            })
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .commands
+            .iter()
+            .map(|command| command.name.clone())
+            .collect();
+        names.sort();
+
+        names
+    }
+}
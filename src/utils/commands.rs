@@ -0,0 +1,876 @@
+use crate::app::App;
+use crate::handler;
+use crate::utils::{
+    buffer_storage::{FileType, State},
+    command_registry::CommandRegistry,
+    ex_command::{ExCommand, Range, RangeContext},
+    file_helper::PathHelper,
+    finder::Finder,
+    keys,
+    marks::Marks,
+    popup::Popup,
+    tree,
+    yank_buffer::{YankBuffer, YankType},
+};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::path::{Path, PathBuf};
+
+// The built-in Ex commands, everything else registers itself next to the
+// feature it belongs to
+pub fn register_defaults(registry: &mut CommandRegistry) {
+    registry.register("q[uit]", quit);
+    registry.register("qa[ll]", quit_all);
+    registry.register("w[rite]", write);
+    registry.register("wq", write_quit);
+    registry.register("x[it]", write_quit);
+    registry.register("reg[isters]", registers);
+    registry.register("di[splay]", registers);
+    registry.register("se[t]", set);
+    registry.register("d[elete]", delete);
+    registry.register("norm[al]", normal);
+    registry.register("cd", change_dir);
+    registry.register("chd[ir]", change_dir);
+    registry.register("e[dit]", edit);
+    registry.register("pw[d]", print_dir);
+    registry.register("mkdir", make_dir);
+    registry.register("touch", touch);
+    registry.register("noh[lsearch]", no_highlight);
+    registry.register("fin[d]", find);
+    registry.register("ju[mps]", jumps);
+    registry.register("ma[rk]", mark);
+    registry.register("marks", marks);
+    registry.register("delm[arks]", delete_marks);
+    registry.register("z", frecent_dir);
+    registry.register("tabnew", tab_new);
+    registry.register("tabe[dit]", tab_new);
+    registry.register("tabc[lose]", tab_close);
+    registry.register("tabn[ext]", tab_next);
+    registry.register("tabp[revious]", tab_previous);
+    registry.register("tabN[ext]", tab_previous);
+    registry.register("tabs", tabs);
+    registry.register("dual[pane]", dual_pane);
+}
+
+pub fn execute(app: &mut App, line: &str) -> Result<(), String> {
+    let command = ExCommand::parse(line)?;
+
+    // `:12` on its own jumps to that line
+    if command.name.is_empty() {
+        if command.range.is_some() {
+            let (_, end) = line_range(app, &command, Range::whole())?;
+            app.cursor.y = end as u16;
+            app.cursor.reset_x();
+        }
+
+        return Ok(());
+    }
+
+    let handler = match app.commands.find(&command.name) {
+        Some(spec) => spec.handler,
+        None => return Err(format!("E492: Not an editor command: {}", line.trim())),
+    };
+
+    handler(app, &command)
+}
+
+// resolves the command's range to inclusive 1-based line numbers, commands
+// typed without one get `default`
+pub fn line_range(
+    app: &App,
+    command: &ExCommand,
+    default: Range,
+) -> Result<(usize, usize), String> {
+    let mark = |mark: char| app.get_mark(mark);
+    let context = RangeContext {
+        current: app.cursor.y as usize,
+        last: app.get_line_count(),
+        mark: &mark,
+    };
+
+    command.range.as_ref().unwrap_or(&default).resolve(&context)
+}
+
+// runs `run` with the cursor on each of the 1-based lines. Like vim's marks
+// the lines move with the ones added or removed around them, a line that
+// was removed on the way, like a deleted directory's branch, is skipped
+pub fn for_each_line(
+    app: &mut App,
+    line_numbers: Vec<usize>,
+    mut run: impl FnMut(&mut App) -> Result<(), String>,
+) -> Result<(), String> {
+    let mut marks: Vec<Option<usize>> = line_numbers.into_iter().map(Some).collect();
+
+    for index in 0..marks.len() {
+        let line_number = match marks[index] {
+            Some(line_number) => line_number,
+            None => continue,
+        };
+        if line_number < 2 || line_number > app.get_line_count() {
+            continue;
+        }
+
+        app.cursor.y = line_number as u16;
+        app.cursor.reset_x();
+
+        let before: Vec<String> = app.buffer_content.lines().map(String::from).collect();
+        run(app)?;
+        let after: Vec<String> = app.buffer_content.lines().map(String::from).collect();
+
+        move_marks(&before, &after, &mut marks[index + 1..]);
+    }
+
+    Ok(())
+}
+
+// the lines both ends have in common stay where they are, the ones in
+// between were changed in place or removed
+fn move_marks(before: &[String], after: &[String], marks: &mut [Option<usize>]) {
+    let prefix = before
+        .iter()
+        .zip(after)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix = before[prefix..]
+        .iter()
+        .rev()
+        .zip(after[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+
+    let changed = before.len() - prefix - suffix;
+    let replaced = after.len() - prefix - suffix;
+
+    for mark in marks.iter_mut() {
+        *mark = mark.and_then(|line_number| {
+            let index = line_number - 1;
+            if index < prefix || changed == replaced {
+                Some(line_number)
+            } else if index >= before.len() - suffix {
+                Some(line_number + after.len() - before.len())
+            } else {
+                None
+            }
+        });
+    }
+}
+
+// with several tabs open only the current one closes, its pending changes
+// stay with the others
+fn quit(app: &mut App, command: &ExCommand) -> Result<(), String> {
+    if app.tabs.count() > 1 {
+        return app.close_tab();
+    }
+
+    quit_all(app, command)
+}
+
+fn quit_all(app: &mut App, command: &ExCommand) -> Result<(), String> {
+    if app.buffer_storage.has_changes() && !command.bang {
+        return Err(String::from(
+            "E37: No write since last change (add ! to override)",
+        ));
+    }
+
+    app.quit();
+    Ok(())
+}
+
+fn write(app: &mut App, _: &ExCommand) -> Result<(), String> {
+    app.save();
+    Ok(())
+}
+
+fn write_quit(app: &mut App, _: &ExCommand) -> Result<(), String> {
+    if app.tabs.count() > 1 {
+        app.save();
+        return app.close_tab();
+    }
+
+    match app.buffer_storage.has_changes() {
+        true => {
+            app.quit_after_save = true;
+            app.save();
+        }
+        false => app.quit(),
+    }
+
+    Ok(())
+}
+
+fn registers(app: &mut App, _: &ExCommand) -> Result<(), String> {
+    app.popup = Some(Popup::new("Registers", app.yank_buffer.describe()));
+    Ok(())
+}
+
+fn jumps(app: &mut App, _: &ExCommand) -> Result<(), String> {
+    let mut lines = vec![String::from(" jump line  directory")];
+
+    for (distance, current, dir) in app.jump_list.describe() {
+        let line = match current {
+            true => app.cursor.y,
+            false => app
+                .positions
+                .get(&dir)
+                .map(|position| position.y)
+                .unwrap_or(1),
+        };
+
+        let marker = match current {
+            true => '>',
+            false => ' ',
+        };
+
+        lines.push(format!("{}{:>4} {:>4}  {}", marker, distance, line, dir));
+    }
+
+    app.popup = Some(Popup::new("Jumps", lines));
+    Ok(())
+}
+
+// `:[range]mark {a-zA-Z}` marks the last line of the range
+fn mark(app: &mut App, command: &ExCommand) -> Result<(), String> {
+    let mut chars = command.args.trim().chars();
+    let mark = match (chars.next(), chars.next()) {
+        (Some(mark), None) => mark,
+        (None, _) => return Err(String::from("E471: Argument required")),
+        _ => {
+            return Err(format!(
+                "E488: Trailing characters: {}",
+                command.args.trim()
+            ))
+        }
+    };
+
+    let (_, end) = line_range(app, command, Range::current())?;
+    let y = app.cursor.y;
+
+    app.cursor.y = end as u16;
+    let result = app.set_mark(mark);
+    app.cursor.y = y;
+
+    result
+}
+
+fn marks(app: &mut App, _: &ExCommand) -> Result<(), String> {
+    show_marks(app)
+}
+
+// the marks of the current directory and every bookmark, d on one deletes it
+fn show_marks(app: &mut App) -> Result<(), String> {
+    let dir = app.path.get_absolute_path();
+
+    let mut lines: Vec<String> = app
+        .marks
+        .locals(&dir)
+        .map(|(mark, entry)| {
+            let line = app.get_mark(*mark).map(|line| line.to_string());
+            format!(" {} {:>5}  {}", mark, line.unwrap_or_default(), entry)
+        })
+        .collect();
+
+    lines.extend(app.marks.globals().map(|(mark, found)| {
+        let path = Path::new(&found.dir).join(&found.entry);
+        format!(" {} {:>5}  {}", mark, found.line, path.to_string_lossy())
+    }));
+
+    if lines.is_empty() {
+        app.popup = None;
+        return Err(String::from("E283: No marks matching"));
+    }
+
+    let selected = app.popup.as_ref().and_then(|popup| popup.selected);
+    let mut popup = Popup::new("Marks (d deletes)", lines).with_delete(delete_listed_mark);
+    popup.select(selected.unwrap_or(0));
+
+    app.popup = Some(popup);
+    Ok(())
+}
+
+fn delete_listed_mark(app: &mut App, line: &str) {
+    let dir = app.path.get_absolute_path();
+
+    if let Some(mark) = line.trim_start().chars().next() {
+        if let Err(error) = app.marks.delete(&dir, mark) {
+            app.echo_error(&format!("Cannot save bookmarks: {}", error));
+        }
+    }
+
+    // the popup closes once the last one is gone
+    let _ = show_marks(app);
+}
+
+// `:delmarks a B c-f` or `:delmarks!` for every mark of the directory
+fn delete_marks(app: &mut App, command: &ExCommand) -> Result<(), String> {
+    let dir = app.path.get_absolute_path();
+
+    if command.bang {
+        if !command.args.trim().is_empty() {
+            return Err(String::from("E474: Invalid argument"));
+        }
+
+        app.marks.clear_local(&dir);
+        return Ok(());
+    }
+
+    let args: Vec<char> = command
+        .args
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+    if args.is_empty() {
+        return Err(String::from("E471: Argument required"));
+    }
+
+    let mut index = 0;
+    while index < args.len() {
+        let (from, to, length) = match args.get(index + 1) {
+            Some('-') if index + 2 < args.len() => (args[index], args[index + 2], 3),
+            _ => (args[index], args[index], 1),
+        };
+        index += length;
+
+        let valid_range = Marks::is_valid(from)
+            && Marks::is_valid(to)
+            && from <= to
+            && from.is_ascii_uppercase() == to.is_ascii_uppercase();
+        if !valid_range {
+            return Err(format!("E475: Invalid argument: {}", command.args.trim()));
+        }
+
+        for mark in from..=to {
+            app.marks
+                .delete(&dir, mark)
+                .map_err(|error| format!("Cannot save bookmarks: {}", error))?;
+        }
+    }
+
+    Ok(())
+}
+
+// `:z foo bar` opens the most frecent directory matching the keywords,
+// without any it lists the ranking
+fn frecent_dir(app: &mut App, command: &ExCommand) -> Result<(), String> {
+    let keywords: Vec<&str> = command.args.split_whitespace().collect();
+    let current = app.path.get_absolute_path();
+
+    let found = app.buffer_storage.frecency.query(&keywords);
+
+    if keywords.is_empty() {
+        if found.is_empty() {
+            return Err(String::from("No directories visited yet"));
+        }
+
+        let lines = found
+            .iter()
+            .map(|(dir, score)| format!("{:>8.1}  {}", score, dir))
+            .collect();
+
+        app.popup = Some(Popup::new("Frecent directories", lines));
+        return Ok(());
+    }
+
+    match found.into_iter().find(|(dir, _)| *dir != current) {
+        Some((dir, _)) => app.open_dir(&dir),
+        None => Err(format!("No directory matches \"{}\"", command.args.trim())),
+    }
+}
+
+fn tab_new(app: &mut App, command: &ExCommand) -> Result<(), String> {
+    let path = command.args.trim();
+
+    app.new_tab(Some(path).filter(|path| !path.is_empty()))
+}
+
+fn tab_close(app: &mut App, _: &ExCommand) -> Result<(), String> {
+    app.close_tab()
+}
+
+// `:tabnext 3` goes to the third tab, 1-based like gt with a count
+fn tab_next(app: &mut App, command: &ExCommand) -> Result<(), String> {
+    match command.args.trim() {
+        "" => app.next_tab(true),
+        number => match number.parse::<usize>() {
+            Ok(number) if (1..=app.tabs.count()).contains(&number) => app.switch_tab(number - 1),
+            _ => return Err(format!("E475: Invalid argument: {}", number)),
+        },
+    }
+
+    Ok(())
+}
+
+fn tab_previous(app: &mut App, command: &ExCommand) -> Result<(), String> {
+    let count = match command.args.trim() {
+        "" => 1,
+        count => count
+            .parse::<usize>()
+            .map_err(|_| format!("E475: Invalid argument: {}", count))?,
+    };
+
+    for _ in 0..count {
+        app.next_tab(false);
+    }
+
+    Ok(())
+}
+
+fn tabs(app: &mut App, _: &ExCommand) -> Result<(), String> {
+    let current = app.path.get_absolute_path();
+    let lines = app
+        .tabs
+        .paths(&current)
+        .into_iter()
+        .enumerate()
+        .map(|(index, dir)| {
+            let marker = match index == app.tabs.current {
+                true => '>',
+                false => ' ',
+            };
+
+            format!("{} {}  {}", marker, index + 1, dir)
+        })
+        .collect();
+
+    app.popup = Some(Popup::new("Tabs", lines));
+    Ok(())
+}
+
+// `:dual` turns the second listing on and off, `:dual {dir}` shows {dir} in
+// it
+fn dual_pane(app: &mut App, command: &ExCommand) -> Result<(), String> {
+    match command.args.trim() {
+        "" if app.dual_pane.is_some() => {
+            app.close_dual_pane();
+            Ok(())
+        }
+        "" => app.open_dual_pane(None),
+        path => app.open_dual_pane(Some(path)),
+    }
+}
+
+fn set(app: &mut App, command: &ExCommand) -> Result<(), String> {
+    if command.args.is_empty() {
+        return Err(String::from("E471: Argument required"));
+    }
+
+    let tree = app.options.tree;
+    for assignment in command.args.split_whitespace() {
+        app.options.set(assignment)?;
+    }
+
+    // indented lines only belong to a directory in tree mode
+    if tree && !app.options.tree {
+        tree::collapse_all(app);
+    }
+
+    Ok(())
+}
+
+// `:[range]d [x] {count}`, like in vim a register can't be a digit so a
+// number is the count of lines to delete from the end of the range on
+fn delete(app: &mut App, command: &ExCommand) -> Result<(), String> {
+    let (start, end) = line_range(app, command, Range::current())?;
+
+    let args = command.args.trim();
+    let (register, count) = match args.chars().next() {
+        Some(register) if !register.is_ascii_digit() => {
+            (Some(register), args[register.len_utf8()..].trim_start())
+        }
+        _ => (None, args),
+    };
+
+    if register.is_some_and(|register| !YankBuffer::is_valid_register(register)) {
+        return Err(format!("E488: Trailing characters: {}", args));
+    }
+
+    let (start, end) = match count {
+        "" => (start, end),
+        count => match count.parse::<usize>() {
+            Ok(0) => return Err(String::from("E939: Positive count required")),
+            Ok(count) => (end, (end + count - 1).min(app.get_line_count())),
+            Err(_) => return Err(format!("E488: Trailing characters: {}", count)),
+        },
+    };
+
+    // "../" can't be deleted
+    let start = start.max(2);
+    if start > end {
+        return Ok(());
+    }
+
+    if let Some(register) = register {
+        app.yank_buffer.select(register);
+    }
+
+    let (removed, dirs): (Vec<String>, Vec<String>) =
+        app.delete_lines(start - 1, end - 1).into_iter().unzip();
+    app.yank_buffer
+        .delete_from(removed.join("\n"), YankType::Line, dirs);
+
+    app.cursor.y = start.min(app.get_line_count()) as u16;
+    app.cursor.reset_x();
+
+    Ok(())
+}
+
+// feeds the keys through the normal key handler once per line, the whole
+// run is a single change
+fn normal(app: &mut App, command: &ExCommand) -> Result<(), String> {
+    if command.args.is_empty() {
+        return Err(String::from("E471: Argument required"));
+    }
+
+    let key_events = keys::parse_keys(&command.args);
+    let (start, end) = line_range(app, command, Range::current())?;
+
+    app.undo_tree.hold();
+    app.macros.playing += 1;
+    let replaying = app.change_recorder.replaying;
+    app.change_recorder.replaying = true;
+
+    let result = run_normal(app, &key_events, start.max(2), end);
+
+    app.change_recorder.replaying = replaying;
+    app.macros.playing -= 1;
+    app.undo_tree.release();
+
+    result
+}
+
+fn run_normal(
+    app: &mut App,
+    key_events: &[KeyEvent],
+    start: usize,
+    end: usize,
+) -> Result<(), String> {
+    for_each_line(app, (start..=end).collect(), |app| {
+        for key_event in key_events {
+            handler::handle_key_events(*key_event, app).map_err(|e| e.to_string())?;
+        }
+
+        // like vim, an unfinished command or insert ends with the keys
+        if !app.is_idle() {
+            let escape = KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE);
+            handler::handle_key_events(escape, app).map_err(|e| e.to_string())?;
+        }
+
+        Ok(())
+    })
+}
+
+// `:cd` alone goes home and `:cd -` back to the previous directory
+fn change_dir(app: &mut App, command: &ExCommand) -> Result<(), String> {
+    let path = match command.args.trim() {
+        "" => String::from("~"),
+        "-" => app
+            .previous_dir
+            .clone()
+            .ok_or(String::from("E186: No previous directory"))?,
+        path => path.to_string(),
+    };
+
+    app.open_dir(&path)?;
+    app.echo(&app.path.get_absolute_path());
+
+    Ok(())
+}
+
+// a directory is opened, a file opens its directory with the cursor on it
+fn edit(app: &mut App, command: &ExCommand) -> Result<(), String> {
+    let path = command.args.trim();
+    if path.is_empty() {
+        // reading the directory again throws the pending changes away
+        if app.buffer_storage.has_changes() && !command.bang {
+            return Err(String::from(
+                "E37: No write since last change (add ! to override)",
+            ));
+        }
+
+        app.reload();
+        return Ok(());
+    }
+
+    let target = PathHelper::expand(path, &app.path.get_absolute_path());
+    if target.is_dir() {
+        return app.open_dir(path);
+    }
+
+    match (target.parent(), target.file_name()) {
+        (Some(dir), Some(name)) if target.exists() => {
+            app.open_dir(&dir.to_string_lossy())?;
+            app.cursor_target = Some(name.to_string_lossy().to_string());
+
+            Ok(())
+        }
+        _ => Err(format!("E447: Can't find file \"{}\"", path)),
+    }
+}
+
+fn print_dir(app: &mut App, _: &ExCommand) -> Result<(), String> {
+    app.echo(&app.path.get_absolute_path());
+    Ok(())
+}
+
+fn make_dir(app: &mut App, command: &ExCommand) -> Result<(), String> {
+    create_entries(app, command, FileType::Directory)
+}
+
+fn touch(app: &mut App, command: &ExCommand) -> Result<(), String> {
+    create_entries(app, command, FileType::File)
+}
+
+// adds the names below the cursor as pending files, nothing is written
+// before the changes are confirmed
+fn create_entries(app: &mut App, command: &ExCommand, file_type: FileType) -> Result<(), String> {
+    let names: Vec<&str> = command
+        .args
+        .split_whitespace()
+        .map(|name| name.trim_end_matches('/'))
+        .collect();
+
+    if names.is_empty() {
+        return Err(String::from("E471: Argument required"));
+    }
+
+    for name in names {
+        if name.contains('/') || name == "." || name == ".." {
+            return Err(format!("E15: Invalid name: {}", name));
+        }
+
+        // in tree mode the entry goes into the branch below the cursor
+        let mut lines: Vec<String> = app.buffer_content.lines().map(String::from).collect();
+        let y = (app.cursor.y as usize).min(lines.len());
+        lines.insert(y, app.indentation_at(y) + name);
+        let dir = app.dir_of(&lines, y);
+
+        let mut view = app
+            .buffer_storage
+            .get_view(&dir)
+            .ok_or(String::from("E499: Directory is not loaded"))?;
+
+        if view
+            .get_file(name)
+            .is_some_and(|file| file.state != State::Deleted)
+        {
+            return Err(format!("E13: File exists: {}", name));
+        }
+
+        app.buffer_content = lines.join("\n");
+
+        // a name that was deleted from the listing comes back instead
+        match view.get_file(name) {
+            Some(_) => view.set_path(name, &dir),
+            None => view.add_file(name, file_type.clone()),
+        }
+        app.buffer_storage.update_view(&dir, view);
+
+        app.cursor.y = y as u16 + 1;
+        app.cursor.reset_x();
+    }
+
+    Ok(())
+}
+
+fn no_highlight(app: &mut App, _: &ExCommand) -> Result<(), String> {
+    app.search.highlight = false;
+    Ok(())
+}
+
+// opens the fuzzy finder, an argument is the initial query
+fn find(app: &mut App, command: &ExCommand) -> Result<(), String> {
+    let finder = Finder::new(&app.path.get_absolute_path(), command.args.trim());
+    app.finder = Some(finder);
+
+    Ok(())
+}
+
+// Tab completion for the command line: command names while the name is being
+// typed, paths relative to the current directory for anything after it
+pub fn complete(app: &App, before_cursor: &str) -> (usize, Vec<String>) {
+    let length = before_cursor.chars().count();
+
+    if let Ok(command) = ExCommand::parse(before_cursor) {
+        let typing_name = command.args.is_empty()
+            && !command.bang
+            && !before_cursor.ends_with(char::is_whitespace)
+            && before_cursor.ends_with(&command.name)
+            && command.name.chars().all(|c| c.is_ascii_alphabetic());
+
+        if typing_name {
+            let names = app
+                .commands
+                .names()
+                .into_iter()
+                .filter(|name| name.starts_with(&command.name))
+                .collect();
+
+            return (length - command.name.chars().count(), names);
+        }
+    }
+
+    let word = before_cursor
+        .rsplit(char::is_whitespace)
+        .next()
+        .unwrap_or_default();
+
+    (length - word.chars().count(), complete_path(app, word))
+}
+
+fn complete_path(app: &App, word: &str) -> Vec<String> {
+    let (dir_part, prefix) = match word.rfind('/') {
+        Some(index) => word.split_at(index + 1),
+        None => ("", word),
+    };
+
+    let home = std::env::var("HOME").unwrap_or_default();
+    let dir = match dir_part {
+        "" => PathBuf::from(app.path.get_absolute_path()),
+        dir if dir.starts_with("~/") => PathBuf::from(&home).join(&dir[2..]),
+        dir => PathBuf::from(app.path.get_absolute_path()).join(dir),
+    };
+
+    let mut candidates: Vec<String> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| {
+                    let name = entry.file_name().to_string_lossy().to_string();
+                    let hidden = name.starts_with('.') && !prefix.starts_with('.');
+
+                    if hidden || !name.starts_with(prefix) {
+                        return None;
+                    }
+
+                    let separator = if entry.path().is_dir() { "/" } else { "" };
+                    Some(format!("{}{}{}", dir_part, name, separator))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    candidates.sort();
+
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn lines(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    // a tree with xdir open on line 4, the entries of a real directory
+    fn tree_app(test: &str) -> (App<'static>, PathBuf) {
+        let root = std::env::temp_dir().join(format!("foil_lines_{}_{}", test, std::process::id()));
+        for dir in ["adir", "bdir", "xdir"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        for file in ["xdir/x1", "xdir/x2", "c", "xa"] {
+            fs::write(root.join(file), "").unwrap();
+        }
+
+        let mut app = App::default();
+        app.options.tree = true;
+        app.open_dir(&root.to_string_lossy()).unwrap();
+        app.buffer_content = app.listing(&app.path).join("\n");
+        app.cursor.y = 4;
+        tree::expand(&mut app).unwrap();
+
+        (app, root)
+    }
+
+    fn buffer(app: &App) -> Vec<String> {
+        app.buffer_content.lines().map(String::from).collect()
+    }
+
+    #[test]
+    fn marks_before_a_change_stay() {
+        let mut marks = [Some(2)];
+        move_marks(&lines(&["a", "b", "c"]), &lines(&["a", "b"]), &mut marks);
+        assert_eq!(marks, [Some(2)]);
+    }
+
+    #[test]
+    fn marks_after_a_change_move_with_it() {
+        let before = lines(&["../", "d", "  e", "  f", "g", "h"]);
+        let mut marks = vec![Some(5), Some(6)];
+        move_marks(&before, &lines(&["../", "g", "h"]), &mut marks);
+        assert_eq!(marks, vec![Some(2), Some(3)]);
+
+        let mut marks = vec![Some(3)];
+        move_marks(
+            &lines(&["../", "a", "b"]),
+            &lines(&["../", "a", "new", "b"]),
+            &mut marks,
+        );
+        assert_eq!(marks, vec![Some(4)]);
+    }
+
+    #[test]
+    fn marks_on_removed_lines_are_dropped() {
+        let before = lines(&["../", "d", "  e", "  f", "g"]);
+        let mut marks = vec![Some(3), Some(4), Some(5)];
+        move_marks(&before, &lines(&["../", "g"]), &mut marks);
+        assert_eq!(marks, vec![None, None, Some(2)]);
+    }
+
+    #[test]
+    fn lines_changed_in_place_keep_their_marks() {
+        let mut marks = vec![Some(3)];
+        move_marks(
+            &lines(&["../", "a", "b"]),
+            &lines(&["../", "A", "B"]),
+            &mut marks,
+        );
+        assert_eq!(marks, vec![Some(3)]);
+    }
+
+    #[test]
+    fn delete_takes_a_count_after_the_register() {
+        let (mut app, root) = tree_app("delete");
+
+        app.cursor.y = 2;
+        execute(&mut app, "d 2").unwrap();
+        assert_eq!(
+            buffer(&app),
+            lines(&["../", "xdir", "  x1", "  x2", "c", "xa"])
+        );
+
+        execute(&mut app, "$d a 1").unwrap();
+        app.yank_buffer.select('a');
+        assert_eq!(
+            app.yank_buffer.get().map(|register| register.content),
+            Some(String::from("xa"))
+        );
+
+        assert!(execute(&mut app, "d 0").is_err());
+        assert!(execute(&mut app, "d a b").is_err());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn global_skips_the_entries_of_a_deleted_branch() {
+        let (mut app, root) = tree_app("global");
+        assert_eq!(
+            buffer(&app),
+            lines(&["../", "adir", "bdir", "xdir", "  x1", "  x2", "c", "xa"])
+        );
+
+        execute(&mut app, "g/x/d").unwrap();
+        assert_eq!(buffer(&app), lines(&["../", "adir", "bdir", "c"]));
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn normal_skips_the_entries_of_a_deleted_branch() {
+        let (mut app, root) = tree_app("normal");
+
+        execute(&mut app, "4,7norm dd").unwrap();
+        assert_eq!(buffer(&app), lines(&["../", "adir", "bdir", "xa"]));
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -1,11 +1,36 @@
 use ratatui::layout::Rect;
 use ratatui::prelude::Position;
 
+// where the cursor was in a directory, what it was on and how far the
+// listing was scrolled when it was last shown
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirPosition {
+    pub x: u16,
+    pub y: u16,
+    pub hovered: Option<String>,
+    pub scroll: u16,
+}
+
+impl Default for DirPosition {
+    fn default() -> Self {
+        Self {
+            x: 1,
+            y: 1,
+            hovered: None,
+            scroll: 0,
+        }
+    }
+}
+
 #[derive(Default, Debug)]
 pub struct Cursor {
     pub container: Option<Rect>,
     pub x: u16,
     pub y: u16,
+    // lines hidden above the view and how many rows the view shows, the
+    // height is only known once the first frame has been drawn
+    pub scroll: u16,
+    pub height: u16,
 }
 
 impl Cursor {
@@ -16,6 +41,8 @@ impl Cursor {
             // since we also need to account for these borders whenever we calculate literally anything
             x: 1,
             y: 1,
+            scroll: 0,
+            height: 0,
         }
     }
 
@@ -24,6 +51,8 @@ impl Cursor {
             container: None,
             x: 1,
             y: 1,
+            scroll: 0,
+            height: 0,
         }
     }
 
@@ -110,7 +139,7 @@ impl Cursor {
         if let Some(container) = self.container {
             let position = Position {
                 x: container.x + self.x,
-                y: container.y + self.y,
+                y: container.y + self.y - self.scroll,
             };
             frame.set_cursor_position(position);
         } else {
@@ -125,4 +154,37 @@ impl Cursor {
             self.x = new_x as u16;
         }
     }
+
+    // the first and last line on screen, 1-based
+    pub fn visible_lines(&self, lines: u16) -> (u16, u16) {
+        let first = self.scroll + 1;
+        let last = (self.scroll + self.height.max(1)).min(lines.max(1));
+
+        (first, last.max(first))
+    }
+
+    // scrolls just enough to keep `margin` lines between the cursor and the
+    // edges of the view, without leaving empty rows below the last line
+    pub fn scroll_into_view(&mut self, margin: u16, lines: u16) {
+        if self.height == 0 {
+            return;
+        }
+
+        let margin = margin.min(self.height.saturating_sub(1) / 2);
+
+        if self.y < self.scroll + 1 + margin {
+            self.scroll = self.y.saturating_sub(1 + margin);
+        }
+
+        if self.y + margin > self.scroll + self.height {
+            self.scroll = self.y + margin - self.height;
+        }
+
+        self.scroll = self.scroll.min(lines.saturating_sub(self.height));
+    }
+
+    // moves the view so the cursor line ends up on the given row, 1-based
+    pub fn scroll_to_row(&mut self, row: u16) {
+        self.scroll = self.y.saturating_sub(row.max(1));
+    }
 }
@@ -0,0 +1,25 @@
+use crate::utils::tabs::Tab;
+
+// Two listings side by side like Midnight Commander. The App works on the
+// focused one, the other is stored away just like an inactive tab
+#[derive(Debug)]
+pub struct DualPane {
+    pub other: Tab,
+    // the side the focused listing is shown on
+    pub focus_left: bool,
+}
+
+impl DualPane {
+    pub fn new(other: Tab) -> Self {
+        Self {
+            other,
+            focus_left: true,
+        }
+    }
+
+    // stores the focused listing and hands out the other one
+    pub fn switch(&mut self, active: Tab) -> Tab {
+        self.focus_left = !self.focus_left;
+        std::mem::replace(&mut self.other, active)
+    }
+}
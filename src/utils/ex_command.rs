@@ -0,0 +1,280 @@
+// Parser for the command line: `[range]name[!] [args]`. Ranges are made of
+// line addresses like vim's: `.`, `$`, `%`, numbers, marks and +/- offsets.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Address {
+    Current,
+    Last,
+    Line(usize),
+    Mark(char),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineAddress {
+    pub address: Address,
+    pub offset: i64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Range {
+    Single(LineAddress),
+    Span(LineAddress, LineAddress),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExCommand {
+    pub range: Option<Range>,
+    pub name: String,
+    pub bang: bool,
+    pub args: String,
+}
+
+// everything the range needs to know about the buffer to turn addresses
+// into line numbers, lines are 1-based
+pub struct RangeContext<'a> {
+    pub current: usize,
+    pub last: usize,
+    pub mark: &'a dyn Fn(char) -> Option<usize>,
+}
+
+impl LineAddress {
+    fn resolve(&self, context: &RangeContext) -> Result<usize, String> {
+        let line = match self.address {
+            Address::Current => context.current,
+            Address::Last => context.last,
+            Address::Line(line) => line,
+            Address::Mark(mark) => {
+                (context.mark)(mark).ok_or(format!("E20: Mark not set: '{}", mark))?
+            }
+        };
+
+        let line = line as i64 + self.offset;
+        if line < 1 || line as usize > context.last {
+            return Err(String::from("E16: Invalid range"));
+        }
+
+        Ok(line as usize)
+    }
+}
+
+impl Range {
+    pub fn current() -> Self {
+        Self::Single(LineAddress {
+            address: Address::Current,
+            offset: 0,
+        })
+    }
+
+    pub fn whole() -> Self {
+        Self::Span(
+            LineAddress {
+                address: Address::Line(1),
+                offset: 0,
+            },
+            LineAddress {
+                address: Address::Last,
+                offset: 0,
+            },
+        )
+    }
+
+    // returns an inclusive, ordered pair of line numbers
+    pub fn resolve(&self, context: &RangeContext) -> Result<(usize, usize), String> {
+        match self {
+            Self::Single(address) => {
+                let line = address.resolve(context)?;
+                Ok((line, line))
+            }
+            Self::Span(start, end) => {
+                let start = start.resolve(context)?;
+                let end = end.resolve(context)?;
+
+                Ok((start.min(end), start.max(end)))
+            }
+        }
+    }
+}
+
+fn parse_number(input: &str) -> (Option<usize>, &str) {
+    let digits = input.chars().take_while(|c| c.is_ascii_digit()).count();
+
+    (input[..digits].parse().ok(), &input[digits..])
+}
+
+fn parse_address(input: &str) -> Result<(Option<LineAddress>, &str), String> {
+    let input = input.trim_start();
+    let mut chars = input.chars();
+
+    let (address, mut rest) = match chars.next() {
+        Some('.') => (Some(Address::Current), &input[1..]),
+        Some('$') => (Some(Address::Last), &input[1..]),
+        Some('\'') => match chars.next() {
+            Some(mark) => (Some(Address::Mark(mark)), &input[1 + mark.len_utf8()..]),
+            None => return Err(String::from("E20: Mark not set")),
+        },
+        Some(c) if c.is_ascii_digit() => {
+            let (number, rest) = parse_number(input);
+            (number.map(Address::Line), rest)
+        }
+        _ => (None, input),
+    };
+
+    let mut offset = 0;
+    while let Some(sign) = rest.chars().next().filter(|c| *c == '+' || *c == '-') {
+        let (number, remaining) = parse_number(&rest[1..]);
+        let amount = number.unwrap_or(1) as i64;
+
+        offset += if sign == '+' { amount } else { -amount };
+        rest = remaining;
+    }
+
+    // `+2` on its own is relative to the current line
+    let address = match (address, offset) {
+        (None, 0) => return Ok((None, rest)),
+        (None, _) => Address::Current,
+        (Some(address), _) => address,
+    };
+
+    Ok((Some(LineAddress { address, offset }), rest))
+}
+
+fn parse_range(input: &str) -> Result<(Option<Range>, &str), String> {
+    let input = input.trim_start();
+
+    if let Some(rest) = input.strip_prefix('%') {
+        return Ok((Some(Range::whole()), rest));
+    }
+
+    let (start, rest) = parse_address(input)?;
+
+    let rest = rest.trim_start();
+    if let Some(rest) = rest.strip_prefix([',', ';']) {
+        let (end, rest) = parse_address(rest)?;
+
+        let current = || LineAddress {
+            address: Address::Current,
+            offset: 0,
+        };
+
+        return Ok((
+            Some(Range::Span(
+                start.unwrap_or_else(current),
+                end.unwrap_or_else(current),
+            )),
+            rest,
+        ));
+    }
+
+    Ok((start.map(Range::Single), rest))
+}
+
+impl ExCommand {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let input = input.trim_start_matches([' ', ':']);
+        let (range, rest) = parse_range(input)?;
+        let rest = rest.trim_start();
+
+        // a command name is either a run of letters or a single symbol, so
+        // `s/a/b/` and `!ls` split the way they do in vim
+        let name_length = match rest.chars().next() {
+            Some(c) if c.is_ascii_alphabetic() => {
+                rest.chars().take_while(|c| c.is_ascii_alphabetic()).count()
+            }
+            Some(c) => c.len_utf8(),
+            None => 0,
+        };
+
+        let name = rest[..name_length].to_string();
+        let rest = &rest[name_length..];
+
+        let (bang, rest) = match rest.strip_prefix('!') {
+            Some(rest) if name != "!" => (true, rest),
+            _ => (false, rest),
+        };
+
+        Ok(Self {
+            range,
+            name,
+            bang,
+            args: rest.trim_start().to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(input: &str) -> Result<(usize, usize), String> {
+        let mark = |mark: char| match mark {
+            'a' => Some(2),
+            'b' => Some(7),
+            _ => None,
+        };
+        let context = RangeContext {
+            current: 4,
+            last: 10,
+            mark: &mark,
+        };
+
+        ExCommand::parse(input)?
+            .range
+            .unwrap_or_else(Range::current)
+            .resolve(&context)
+    }
+
+    #[test]
+    fn splits_name_bang_and_args() {
+        let command = ExCommand::parse(":w! foo").unwrap();
+        assert_eq!(command.range, None);
+        assert_eq!(command.name, "w");
+        assert!(command.bang);
+        assert_eq!(command.args, "foo");
+
+        let command = ExCommand::parse("%s/a/b/g").unwrap();
+        assert_eq!(command.range, Some(Range::whole()));
+        assert_eq!(command.name, "s");
+        assert_eq!(command.args, "/a/b/g");
+
+        let command = ExCommand::parse("!ls -a").unwrap();
+        assert_eq!(command.name, "!");
+        assert!(!command.bang);
+        assert_eq!(command.args, "ls -a");
+    }
+
+    #[test]
+    fn resolves_addresses() {
+        assert_eq!(resolve("d"), Ok((4, 4)));
+        assert_eq!(resolve("2,3d"), Ok((2, 3)));
+        assert_eq!(resolve(".,$d"), Ok((4, 10)));
+        assert_eq!(resolve("%d"), Ok((1, 10)));
+        assert_eq!(resolve("'a,'bd"), Ok((2, 7)));
+    }
+
+    #[test]
+    fn applies_offsets() {
+        assert_eq!(resolve("+2d"), Ok((6, 6)));
+        assert_eq!(resolve("-d"), Ok((3, 3)));
+        assert_eq!(resolve("$-1d"), Ok((9, 9)));
+        assert_eq!(resolve(".+1+1,'b-2d"), Ok((5, 6)));
+        assert_eq!(resolve(",+3d"), Ok((4, 7)));
+    }
+
+    #[test]
+    fn orders_backwards_ranges() {
+        assert_eq!(resolve("5,2d"), Ok((2, 5)));
+    }
+
+    #[test]
+    fn rejects_lines_outside_the_buffer() {
+        assert_eq!(resolve("0d"), Err(String::from("E16: Invalid range")));
+        assert_eq!(resolve("$+1d"), Err(String::from("E16: Invalid range")));
+        assert_eq!(resolve("1,11d"), Err(String::from("E16: Invalid range")));
+    }
+
+    #[test]
+    fn rejects_unknown_marks() {
+        assert_eq!(resolve("'zd"), Err(String::from("E20: Mark not set: 'z")));
+        assert!(ExCommand::parse("'").is_err());
+    }
+}
@@ -215,6 +215,10 @@ impl PathHelper {
     }
 
     pub fn get_absolute_path(&self) -> String {
+        if self.current_path.is_absolute() {
+            return self.current_path.to_string_lossy().to_string();
+        }
+
         let mut temp = self
             .current_path
             .to_str()
@@ -238,6 +242,60 @@ impl PathHelper {
         }
     }
 
+    // turns user input into an absolute path: `~` and `$VAR`/`${VAR}` are
+    // expanded and relative paths start at `base`
+    pub fn expand(input: &str, base: &str) -> PathBuf {
+        let mut expanded = String::new();
+        let mut rest = input;
+
+        if rest == "~" || rest.starts_with("~/") {
+            expanded.push_str(&std::env::var("HOME").unwrap_or_default());
+            rest = &rest[1..];
+        }
+
+        while let Some(index) = rest.find('$') {
+            expanded.push_str(&rest[..index]);
+            rest = &rest[index + 1..];
+
+            let (name, remaining) = match rest.strip_prefix('{') {
+                Some(braced) => match braced.find('}') {
+                    Some(end) => (&braced[..end], &braced[end + 1..]),
+                    None => (braced, ""),
+                },
+                None => {
+                    let end = rest
+                        .find(|c: char| !c.is_alphanumeric() && c != '_')
+                        .unwrap_or(rest.len());
+                    (&rest[..end], &rest[end..])
+                }
+            };
+
+            match name.is_empty() {
+                true => expanded.push('$'),
+                false => expanded.push_str(&std::env::var(name).unwrap_or_default()),
+            }
+            rest = remaining;
+        }
+        expanded.push_str(rest);
+
+        let path = Path::new(base).join(expanded);
+
+        // `..` and `.` are resolved by hand so paths that don't exist yet
+        // work too
+        let mut normalized = PathBuf::from("/");
+        for component in path.components() {
+            match component {
+                std::path::Component::ParentDir => {
+                    normalized.pop();
+                }
+                std::path::Component::Normal(part) => normalized.push(part),
+                _ => {}
+            }
+        }
+
+        normalized
+    }
+
     pub fn trim_path(path: &str) -> String {
         let parts = path.split('/').collect::<Vec<&str>>();
         let file_name = parts.last().unwrap_or(&"");
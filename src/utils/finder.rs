@@ -0,0 +1,252 @@
+use crate::utils::command_line::CommandLine;
+use ignore::WalkBuilder;
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+// walking stops here so huge trees don't fill up the memory
+const MAX_ENTRIES: usize = 100_000;
+// how many paths the walk hands over at once
+const BATCH_SIZE: usize = 1_000;
+
+#[derive(Debug, Clone)]
+pub struct Match {
+    pub path: String,
+    pub score: i64,
+    // char indices of the matched characters, for highlighting
+    pub positions: Vec<usize>,
+}
+
+// Fuzzy picker over every file below `root`, hidden files and whatever
+// .gitignore/.ignore exclude are left out. Directories end in a slash. The
+// tree is walked on its own thread, files show up as they are found
+#[derive(Debug)]
+pub struct Finder {
+    pub line: CommandLine,
+    pub root: String,
+    files: Vec<String>,
+    walk: Option<Receiver<Vec<String>>>,
+    pub matches: Vec<Match>,
+    pub selected: usize,
+}
+
+// stops early once the finder is closed and nobody receives anymore
+fn walk(root: String, sender: mpsc::Sender<Vec<String>>) {
+    let mut batch = Vec::with_capacity(BATCH_SIZE);
+
+    for entry in WalkBuilder::new(&root)
+        .require_git(false)
+        .build()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.depth() > 0)
+        .take(MAX_ENTRIES)
+    {
+        let Ok(relative) = entry.path().strip_prefix(&root) else {
+            continue;
+        };
+        let mut path = relative.to_string_lossy().to_string();
+
+        if entry
+            .file_type()
+            .is_some_and(|file_type| file_type.is_dir())
+        {
+            path.push('/');
+        }
+
+        batch.push(path);
+        if batch.len() == BATCH_SIZE && sender.send(std::mem::take(&mut batch)).is_err() {
+            return;
+        }
+    }
+
+    let _ = sender.send(batch);
+}
+
+impl Finder {
+    pub fn new(root: &str, query: &str) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let walk_root = root.to_string();
+        thread::spawn(move || walk(walk_root, sender));
+
+        let mut finder = Self {
+            line: CommandLine::new("finder_history"),
+            root: root.to_string(),
+            files: vec![],
+            walk: Some(receiver),
+            matches: vec![],
+            selected: 0,
+        };
+        finder.line.open(query);
+        finder.poll();
+        finder.update();
+
+        finder
+    }
+
+    pub fn is_walking(&self) -> bool {
+        self.walk.is_some()
+    }
+
+    // takes what the walk found so far, the selection stays where it is
+    pub fn poll(&mut self) {
+        let Some(receiver) = &self.walk else {
+            return;
+        };
+
+        let mut found = false;
+        loop {
+            match receiver.try_recv() {
+                Ok(batch) => {
+                    self.files.extend(batch);
+                    found = true;
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.walk = None;
+                    break;
+                }
+            }
+        }
+
+        if found {
+            self.rank();
+            self.selected = self.selected.min(self.matches.len().saturating_sub(1));
+        }
+    }
+
+    // ranks every file against what is typed, best first
+    pub fn update(&mut self) {
+        self.rank();
+        self.selected = 0;
+    }
+
+    fn rank(&mut self) {
+        let query = &self.line.content;
+
+        self.matches = self
+            .files
+            .iter()
+            .filter_map(|path| {
+                score(path, query).map(|(score, positions)| (path, score, positions))
+            })
+            .map(|(path, score, positions)| Match {
+                path: path.clone(),
+                score,
+                positions,
+            })
+            .collect();
+
+        self.matches
+            .sort_by(|a, b| b.score.cmp(&a.score).then(a.path.len().cmp(&b.path.len())));
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.matches.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    // the directory to open and the entry to put the cursor on
+    pub fn target(&self) -> Option<(String, String)> {
+        let selected = self.matches.get(self.selected)?;
+        let path = Path::new(&self.root).join(selected.path.trim_end_matches('/'));
+
+        let dir = path.parent()?.to_string_lossy().to_string();
+        let name = path.file_name()?.to_string_lossy().to_string();
+
+        Some((dir, name))
+    }
+}
+
+fn is_boundary(previous: Option<char>) -> bool {
+    match previous {
+        None => true,
+        Some(c) => "/_-. ".contains(c),
+    }
+}
+
+// fzf style scoring: every query char has to appear in order. The tightest
+// window ending at the first full match is used, matches at the start of a
+// word, runs of matches and hits in the file name itself score higher
+pub fn score(path: &str, query: &str) -> Option<(i64, Vec<usize>)> {
+    let case_sensitive = query.chars().any(|c| c.is_uppercase());
+    let normalize = |c: char| match case_sensitive {
+        true => c,
+        false => c.to_ascii_lowercase(),
+    };
+
+    let chars: Vec<char> = path.chars().map(normalize).collect();
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(normalize)
+        .collect();
+
+    if query.is_empty() {
+        return Some((0, vec![]));
+    }
+
+    // forward to the first position where the whole query has matched
+    let mut index = 0;
+    let mut end = None;
+    for (position, c) in chars.iter().enumerate() {
+        if *c == query[index] {
+            index += 1;
+            if index == query.len() {
+                end = Some(position);
+                break;
+            }
+        }
+    }
+    let end = end?;
+
+    // backward from there to find where the tightest match starts
+    let mut index = query.len();
+    let mut start = end;
+    for position in (0..=end).rev() {
+        if chars[position] == query[index - 1] {
+            index -= 1;
+            if index == 0 {
+                start = position;
+                break;
+            }
+        }
+    }
+
+    let original: Vec<char> = path.chars().collect();
+    let name_start = path
+        .trim_end_matches('/')
+        .rfind('/')
+        .map(|index| path[..index].chars().count() + 1)
+        .unwrap_or(0);
+
+    let mut positions = Vec::with_capacity(query.len());
+    let mut score = 0;
+    let mut index = 0;
+    for (position, c) in chars.iter().enumerate().take(end + 1).skip(start) {
+        if index < query.len() && *c == query[index] {
+            score += 16;
+
+            if is_boundary(position.checked_sub(1).map(|previous| original[previous])) {
+                score += 10;
+            }
+            if position > 0 && positions.last() == Some(&(position - 1)) {
+                score += 8;
+            }
+            if position >= name_start {
+                score += 4;
+            }
+
+            positions.push(position);
+            index += 1;
+        } else {
+            score -= 1;
+        }
+    }
+
+    Some((score, positions))
+}
@@ -0,0 +1,238 @@
+use crate::utils::{
+    marks::{escape, unescape},
+    system,
+};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const FRECENCY_FILE: &str = "frecency";
+// once the ranks add up to this, they all shrink so old entries fade away
+const MAX_RANK: f64 = 10_000.0;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Entry {
+    dir: String,
+    rank: f64,
+    last_visit: u64,
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+// Every visited directory with how often and when it was last opened, the
+// way zoxide ranks them. Stored in the data directory as one
+// `{rank}\t{last visit}\t{dir}` per line with the dir escaped like a
+// bookmark, visits are written in batches.
+#[derive(Debug, Default)]
+pub struct Frecency {
+    entries: Vec<Entry>,
+    // visits that aren't in the file yet
+    dirty: bool,
+}
+
+impl Frecency {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load() -> Self {
+        Self::parse(&system::read_data_file(FRECENCY_FILE).unwrap_or_default())
+    }
+
+    fn parse(content: &str) -> Self {
+        let entries = content
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(3, '\t');
+
+                Some(Entry {
+                    rank: fields.next()?.parse().ok()?,
+                    last_visit: fields.next()?.parse().ok()?,
+                    dir: unescape(fields.next()?),
+                })
+            })
+            .collect();
+
+        Self {
+            entries,
+            dirty: false,
+        }
+    }
+
+    fn content(&self) -> String {
+        self.entries
+            .iter()
+            .map(|entry| {
+                format!(
+                    "{}\t{}\t{}",
+                    entry.rank,
+                    entry.last_visit,
+                    escape(&entry.dir)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn save(&self) -> Result<(), std::io::Error> {
+        system::write_data_file(FRECENCY_FILE, &self.content())
+    }
+
+    pub fn visit(&mut self, dir: &str) {
+        match self.entries.iter_mut().find(|entry| entry.dir == dir) {
+            Some(entry) => {
+                entry.rank += 1.0;
+                entry.last_visit = now();
+            }
+            None => self.entries.push(Entry {
+                dir: dir.to_string(),
+                rank: 1.0,
+                last_visit: now(),
+            }),
+        }
+
+        let total: f64 = self.entries.iter().map(|entry| entry.rank).sum();
+        if total > MAX_RANK {
+            for entry in self.entries.iter_mut() {
+                entry.rank *= 0.9;
+            }
+            self.entries.retain(|entry| entry.rank >= 1.0);
+        }
+
+        self.dirty = true;
+    }
+
+    // writes the visits made since the last time, a failed write isn't
+    // tried again until the next visit
+    pub fn flush(&mut self) -> Result<(), std::io::Error> {
+        match std::mem::take(&mut self.dirty) {
+            true => self.save(),
+            false => Ok(()),
+        }
+    }
+
+    // recent visits count for more than old ones
+    fn score(entry: &Entry, now: u64) -> f64 {
+        let age = now.saturating_sub(entry.last_visit);
+
+        let factor = match age {
+            age if age < 60 * 60 => 4.0,
+            age if age < 60 * 60 * 24 => 2.0,
+            age if age < 60 * 60 * 24 * 7 => 0.5,
+            _ => 0.25,
+        };
+
+        entry.rank * factor
+    }
+
+    // directories that still exist and match every keyword, best first
+    pub fn query(&self, keywords: &[&str]) -> Vec<(String, f64)> {
+        let now = now();
+
+        let mut found: Vec<(String, f64)> = self
+            .entries
+            .iter()
+            .filter(|entry| matches(&entry.dir, keywords))
+            .filter(|entry| Path::new(&entry.dir).is_dir())
+            .map(|entry| (entry.dir.clone(), Self::score(entry, now)))
+            .collect();
+
+        found.sort_by(|a, b| b.1.total_cmp(&a.1));
+        found
+    }
+}
+
+// like zoxide: the keywords appear in the path in order and the last one is
+// part of the last component. Lowercase keywords ignore case
+pub fn matches(dir: &str, keywords: &[&str]) -> bool {
+    let mut rest = dir;
+
+    for keyword in keywords {
+        let case_sensitive = keyword.chars().any(|c| c.is_uppercase());
+        let haystack = match case_sensitive {
+            true => rest.to_string(),
+            false => rest.to_ascii_lowercase(),
+        };
+
+        match haystack.find(keyword) {
+            Some(index) => rest = &rest[index + keyword.len()..],
+            None => return false,
+        }
+    }
+
+    let last_component = dir
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or_default();
+    match keywords.last() {
+        Some(keyword) if keyword.chars().any(|c| c.is_uppercase()) => {
+            last_component.contains(keyword)
+        }
+        Some(keyword) => last_component.to_ascii_lowercase().contains(keyword),
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keywords_match_in_order() {
+        assert!(matches("/home/user/projects/foil", &["proj", "foil"]));
+        assert!(!matches("/home/user/projects/foil", &["foil", "proj"]));
+    }
+
+    #[test]
+    fn last_keyword_is_in_the_last_component() {
+        assert!(matches("/home/user/projects/foil", &["oi"]));
+        assert!(!matches("/home/user/projects/foil", &["proj"]));
+        assert!(matches("/home/user/projects/foil/", &["foil"]));
+    }
+
+    #[test]
+    fn lowercase_keywords_ignore_case() {
+        assert!(matches("/home/user/Documents", &["doc"]));
+        assert!(matches("/home/user/Documents", &["Doc"]));
+        assert!(!matches("/home/user/documents", &["Doc"]));
+        assert!(!matches("/home/User/docs", &["User", "Docs"]));
+    }
+
+    #[test]
+    fn no_keywords_match_everything() {
+        assert!(matches("/anywhere", &[]));
+    }
+
+    #[test]
+    fn recent_visits_rank_higher() {
+        let now = now();
+        let recent = Entry {
+            dir: String::from("/recent"),
+            rank: 1.0,
+            last_visit: now,
+        };
+        let old = Entry {
+            dir: String::from("/old"),
+            rank: 4.0,
+            last_visit: now - 60 * 60 * 24 * 30,
+        };
+
+        assert!(Frecency::score(&recent, now) > Frecency::score(&old, now));
+    }
+
+    #[test]
+    fn dirs_with_separators_survive_a_round_trip() {
+        let mut frecency = Frecency::new();
+        frecency.visit("/tmp/tab\there");
+        frecency.visit("/tmp/new\nline");
+
+        let content = frecency.content();
+        assert_eq!(content.lines().count(), 2);
+        assert_eq!(Frecency::parse(&content).entries, frecency.entries);
+    }
+}
@@ -0,0 +1,74 @@
+use crate::app::App;
+use crate::utils::{
+    command_registry::CommandRegistry,
+    commands,
+    ex_command::{ExCommand, Range},
+    popup::Popup,
+    substitute::split_delimited,
+};
+use regex::Regex;
+
+pub fn register(registry: &mut CommandRegistry) {
+    registry.register("g[lobal]", global);
+    registry.register("v[global]", global);
+}
+
+// `:g/pattern/cmd` runs cmd on every matching line, `:v` and `:g!` on every
+// line that doesn't match. Without a command the matches are listed
+fn global(app: &mut App, command: &ExCommand) -> Result<(), String> {
+    let inverted = command.bang || command.name.starts_with('v');
+
+    let delimiter = match command.args.chars().next() {
+        Some(c) if !c.is_alphanumeric() && !c.is_whitespace() && c != '\\' => c,
+        _ => return Err(String::from("E476: Invalid command")),
+    };
+
+    let (pattern, rest) = split_delimited(&command.args[delimiter.len_utf8()..], delimiter);
+    let pattern =
+        Regex::new(&pattern).map_err(|_| format!("E486: Invalid pattern: {}", pattern))?;
+    let subcommand = rest.unwrap_or("").trim().to_string();
+
+    if let Ok(nested) = ExCommand::parse(&subcommand) {
+        if let Some(spec) = app.commands.find(&nested.name) {
+            if spec.name == "global" || spec.name == "vglobal" {
+                return Err(String::from("E147: Cannot do :global recursive"));
+            }
+        }
+    }
+
+    // "../" never takes part, it can't be changed anyway
+    let (start, end) = commands::line_range(app, command, Range::whole())?;
+    let marked: Vec<(usize, String)> = app
+        .buffer_content
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.to_string()))
+        .filter(|(line_number, line)| {
+            *line_number > 1
+                && (start..=end).contains(line_number)
+                && pattern.is_match(line) != inverted
+        })
+        .collect();
+
+    if marked.is_empty() {
+        return Err(format!("E486: Pattern not found: {}", pattern.as_str()));
+    }
+
+    if subcommand.is_empty() {
+        let lines = marked
+            .into_iter()
+            .map(|(line_number, line)| format!("{:>4} {}", line_number, line))
+            .collect();
+
+        app.popup = Some(Popup::new("Global", lines));
+        return Ok(());
+    }
+
+    let line_numbers = marked
+        .into_iter()
+        .map(|(line_number, _)| line_number)
+        .collect();
+    commands::for_each_line(app, line_numbers, |app| commands::execute(app, &subcommand))?;
+
+    Ok(())
+}
@@ -18,7 +18,7 @@ impl InputBuffer {
             ],
             buffer: String::new(),
             pattern: String::from(
-                r#"^(([cdy][ai][wWeEbB\(\)\[\]\{\}"'`])|([cd]f.)|(g[gfd])|([cd][GwWeEbBhjkl\{\}$0])|([cd](gg|ga))|(\d+[hjklwWbBeE])|(\d+[cd][wWeEbB])|yy|cc|dd|([cd][fFtT].))$"#,
+                r#"^(([cdy][ai][wWeEbBns\(\)\[\]\{\}<>"'`])|([cd]f.)|(g[gfdltTy\-+])|([cdy][GwWeEbBhjkl\{\}$0])|([cd](gg|ga))|(\d+[hjklwWbBeE])|(\d+[cdy][wWeEbB])|yy|cc|dd|([cd][fFtT].)|(q[a-zA-Z])|(z[ztboca])|(m[a-zA-Z])|(['`][a-zA-Z<>])|(\d*@[a-z@])|("[a-zA-Z0-9"\-_+*]))$"#,
             ),
         }
     }
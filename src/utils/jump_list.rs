@@ -0,0 +1,171 @@
+use std::path::Path;
+
+const MAX_JUMPS: usize = 100;
+
+// Directories in the order they were opened, newest last. Ctrl-o and Ctrl-i
+// walk it like vim's jump list: opening a directory while somewhere in the
+// middle drops what came after, and a directory is only kept once
+#[derive(Debug, Clone, Default)]
+pub struct JumpList {
+    entries: Vec<String>,
+    index: usize,
+}
+
+impl JumpList {
+    pub fn new(start: &str) -> Self {
+        Self {
+            entries: vec![start.to_string()],
+            index: 0,
+        }
+    }
+
+    pub fn record(&mut self, dir: &str) {
+        if self
+            .entries
+            .get(self.index)
+            .is_some_and(|current| current == dir)
+        {
+            return;
+        }
+
+        self.entries.truncate(self.index + 1);
+        self.entries.retain(|entry| entry != dir);
+        self.entries.push(dir.to_string());
+
+        let overflow = self.entries.len().saturating_sub(MAX_JUMPS);
+        self.entries.drain(..overflow);
+
+        self.index = self.entries.len() - 1;
+    }
+
+    // directories that were removed meanwhile are skipped
+    pub fn back(&mut self) -> Option<String> {
+        let index = (0..self.index)
+            .rev()
+            .find(|index| Path::new(&self.entries[*index]).is_dir())?;
+
+        self.index = index;
+        Some(self.entries[index].clone())
+    }
+
+    pub fn forward(&mut self) -> Option<String> {
+        let index = (self.index + 1..self.entries.len())
+            .find(|index| Path::new(&self.entries[*index]).is_dir())?;
+
+        self.index = index;
+        Some(self.entries[index].clone())
+    }
+
+    // every entry with its distance from the current one, like `:jumps`
+    pub fn describe(&self) -> Vec<(usize, bool, String)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .map(|(index, dir)| (index.abs_diff(self.index), index == self.index, dir.clone()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    // real directories, the list skips the ones that are gone
+    fn dirs(test: &str, names: &[&str]) -> (PathBuf, Vec<String>) {
+        let root = std::env::temp_dir().join(format!("foil_jumps_{}_{}", test, std::process::id()));
+        let dirs = names
+            .iter()
+            .map(|name| {
+                let dir = root.join(name);
+                fs::create_dir_all(&dir).unwrap();
+                dir.to_string_lossy().to_string()
+            })
+            .collect();
+
+        (root, dirs)
+    }
+
+    fn entries(jumps: &JumpList) -> Vec<String> {
+        jumps
+            .describe()
+            .into_iter()
+            .map(|(_, _, dir)| dir)
+            .collect()
+    }
+
+    #[test]
+    fn walks_back_and_forward() {
+        let (root, dirs) = dirs("walk", &["a", "b", "c"]);
+        let mut jumps = JumpList::new(&dirs[0]);
+        jumps.record(&dirs[1]);
+        jumps.record(&dirs[2]);
+
+        assert_eq!(jumps.forward(), None);
+        assert_eq!(jumps.back().as_ref(), Some(&dirs[1]));
+        assert_eq!(jumps.back().as_ref(), Some(&dirs[0]));
+        assert_eq!(jumps.back(), None);
+        assert_eq!(jumps.forward().as_ref(), Some(&dirs[1]));
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn recording_after_going_back_drops_the_newer_entries() {
+        let (root, dirs) = dirs("truncate", &["a", "b", "c", "d"]);
+        let mut jumps = JumpList::new(&dirs[0]);
+        jumps.record(&dirs[1]);
+        jumps.record(&dirs[2]);
+
+        jumps.back();
+        jumps.back();
+        jumps.record(&dirs[3]);
+
+        assert_eq!(entries(&jumps), vec![dirs[0].clone(), dirs[3].clone()]);
+        assert_eq!(jumps.forward(), None);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn keeps_a_directory_once() {
+        let (root, dirs) = dirs("once", &["a", "b"]);
+        let mut jumps = JumpList::new(&dirs[0]);
+        jumps.record(&dirs[0]);
+        jumps.record(&dirs[1]);
+        jumps.record(&dirs[0]);
+
+        assert_eq!(entries(&jumps), vec![dirs[1].clone(), dirs[0].clone()]);
+        assert_eq!(jumps.describe()[1], (0, true, dirs[0].clone()));
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn skips_removed_directories() {
+        let (root, dirs) = dirs("removed", &["a", "b", "c"]);
+        let mut jumps = JumpList::new(&dirs[0]);
+        jumps.record(&dirs[1]);
+        jumps.record(&dirs[2]);
+
+        fs::remove_dir(&dirs[1]).unwrap();
+        assert_eq!(jumps.back().as_ref(), Some(&dirs[0]));
+        assert_eq!(jumps.forward().as_ref(), Some(&dirs[2]));
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn forgets_the_oldest_entries() {
+        let mut jumps = JumpList::new("/0");
+        for index in 1..=MAX_JUMPS + 10 {
+            jumps.record(&format!("/{}", index));
+        }
+
+        let entries = entries(&jumps);
+        assert_eq!(entries.len(), MAX_JUMPS);
+        assert_eq!(entries.first().map(String::as_str), Some("/11"));
+        assert_eq!(jumps.describe().last().map(|entry| entry.0), Some(0));
+    }
+}
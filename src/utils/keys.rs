@@ -0,0 +1,188 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+// Vim style key notation (`<Esc>`, `<CR>`, `<C-r>`, ...) used to store macros
+// and to turn typed strings into key events
+
+const SPECIAL_KEYS: [(&str, KeyCode); 17] = [
+    ("Esc", KeyCode::Esc),
+    ("CR", KeyCode::Enter),
+    ("Enter", KeyCode::Enter),
+    ("BS", KeyCode::Backspace),
+    ("Tab", KeyCode::Tab),
+    ("S-Tab", KeyCode::BackTab),
+    ("Del", KeyCode::Delete),
+    ("Up", KeyCode::Up),
+    ("Down", KeyCode::Down),
+    ("Left", KeyCode::Left),
+    ("Right", KeyCode::Right),
+    ("Home", KeyCode::Home),
+    ("End", KeyCode::End),
+    ("PageUp", KeyCode::PageUp),
+    ("PageDown", KeyCode::PageDown),
+    ("Insert", KeyCode::Insert),
+    ("Space", KeyCode::Char(' ')),
+];
+
+const MODIFIERS: [(&str, KeyModifiers); 4] = [
+    ("C-", KeyModifiers::CONTROL),
+    ("A-", KeyModifiers::ALT),
+    ("M-", KeyModifiers::ALT),
+    ("S-", KeyModifiers::SHIFT),
+];
+
+fn parse_code(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+
+    if name.eq_ignore_ascii_case("lt") {
+        return Some(KeyCode::Char('<'));
+    }
+
+    if let Some(number) = name.strip_prefix(['F', 'f']) {
+        return number.parse().ok().map(KeyCode::F);
+    }
+
+    SPECIAL_KEYS
+        .iter()
+        .find(|(special, _)| special.eq_ignore_ascii_case(name))
+        .map(|(_, code)| *code)
+}
+
+// modifiers come first, `<C-A-x>` or `<S-F5>`
+fn parse_special(name: &str) -> Option<KeyEvent> {
+    if let Some(code) = parse_code(name) {
+        return Some(KeyEvent::new(code, KeyModifiers::NONE));
+    }
+
+    let mut modifiers = KeyModifiers::NONE;
+    let mut rest = name;
+
+    while let Some((prefix, modifier)) = MODIFIERS.iter().find(|(prefix, _)| {
+        rest.len() > prefix.len()
+            && rest
+                .get(..prefix.len())
+                .is_some_and(|start| start.eq_ignore_ascii_case(prefix))
+    }) {
+        modifiers |= *modifier;
+        rest = &rest[prefix.len()..];
+    }
+
+    if modifiers.is_empty() {
+        return None;
+    }
+
+    parse_code(rest).map(|code| KeyEvent::new(code, modifiers))
+}
+
+pub fn parse_keys(input: &str) -> Vec<KeyEvent> {
+    let mut keys = Vec::new();
+    let mut rest = input;
+
+    while let Some(c) = rest.chars().next() {
+        if c == '<' {
+            if let Some(end) = rest.find('>') {
+                if let Some(key) = parse_special(&rest[1..end]) {
+                    keys.push(key);
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+            }
+        }
+
+        keys.push(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        rest = &rest[c.len_utf8()..];
+    }
+
+    keys
+}
+
+fn format_code(code: KeyCode) -> Option<String> {
+    match code {
+        KeyCode::Char('<') => Some(String::from("lt")),
+        KeyCode::Char(c) => Some(c.to_string()),
+        KeyCode::F(number) => Some(format!("F{}", number)),
+        code => SPECIAL_KEYS
+            .iter()
+            .find(|(_, special)| *special == code)
+            .map(|(name, _)| name.to_string()),
+    }
+}
+
+// None for keys that have no notation
+pub fn format_key(key_event: &KeyEvent) -> Option<String> {
+    let name = format_code(key_event.code)?;
+
+    // the case of a letter already says whether shift was held
+    let mut modifiers = key_event.modifiers;
+    if let KeyCode::Char(_) | KeyCode::BackTab = key_event.code {
+        modifiers.remove(KeyModifiers::SHIFT);
+    }
+
+    let prefix: String = MODIFIERS
+        .iter()
+        .filter(|(prefix, _)| *prefix != "M-")
+        .filter(|(_, modifier)| modifiers.contains(*modifier))
+        .map(|(prefix, _)| *prefix)
+        .collect();
+
+    match (key_event.code, prefix.is_empty()) {
+        (KeyCode::Char(c), true) if c != '<' => Some(name),
+        _ => Some(format!("<{}{}>", prefix, name)),
+    }
+}
+
+pub fn format_keys(keys: &[KeyEvent]) -> Option<String> {
+    keys.iter().map(format_key).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn round_trips_modified_and_function_keys() {
+        let keys = vec![
+            key(KeyCode::Char('a'), KeyModifiers::NONE),
+            key(KeyCode::Char('<'), KeyModifiers::NONE),
+            key(KeyCode::Char('r'), KeyModifiers::CONTROL),
+            key(KeyCode::Char('x'), KeyModifiers::ALT),
+            key(KeyCode::F(5), KeyModifiers::NONE),
+            key(KeyCode::F(12), KeyModifiers::SHIFT),
+            key(KeyCode::PageDown, KeyModifiers::NONE),
+            key(KeyCode::Esc, KeyModifiers::NONE),
+        ];
+
+        let notation = format_keys(&keys).unwrap();
+        assert_eq!(notation, "a<lt><C-r><A-x><F5><S-F12><PageDown><Esc>");
+        assert_eq!(parse_keys(&notation), keys);
+    }
+
+    #[test]
+    fn shift_is_part_of_the_letter() {
+        let key = key(KeyCode::Char('A'), KeyModifiers::SHIFT);
+        assert_eq!(format_key(&key).as_deref(), Some("A"));
+    }
+
+    #[test]
+    fn keys_without_notation_are_refused() {
+        let keys = [key(KeyCode::CapsLock, KeyModifiers::NONE)];
+        assert_eq!(format_keys(&keys), None);
+    }
+
+    #[test]
+    fn unknown_notation_stays_literal() {
+        assert_eq!(
+            parse_keys("<x-y>"),
+            "<x-y>"
+                .chars()
+                .map(|c| key(KeyCode::Char(c), KeyModifiers::NONE))
+                .collect::<Vec<_>>()
+        );
+    }
+}
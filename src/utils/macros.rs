@@ -0,0 +1,111 @@
+use crate::utils::{keys, system};
+use crossterm::event::KeyEvent;
+use std::collections::HashMap;
+
+const MACRO_FILE: &str = "macros";
+
+// q{reg} records every key that passes through the handler into a register,
+// @{reg} feeds them back. Registers are stored as key notation, one per line.
+#[derive(Debug, Default)]
+pub struct Macros {
+    registers: HashMap<char, Vec<KeyEvent>>,
+    recording: Option<char>,
+    keys: Vec<KeyEvent>,
+    last_played: Option<char>,
+    pub playing: usize,
+}
+
+impl Macros {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load() -> Self {
+        let mut macros = Self::new();
+
+        for line in system::read_data_file(MACRO_FILE)
+            .unwrap_or_default()
+            .lines()
+        {
+            let mut chars = line.chars();
+            if let (Some(register), Some(':')) = (chars.next(), chars.next()) {
+                macros
+                    .registers
+                    .insert(register, keys::parse_keys(chars.as_str()));
+            }
+        }
+
+        macros
+    }
+
+    // a register holding a key without notation is not saved at all rather
+    // than replayed without it next time
+    pub fn save(&self) -> Result<(), std::io::Error> {
+        let mut registers: Vec<_> = self.registers.iter().collect();
+        registers.sort_by_key(|(register, _)| **register);
+
+        let mut skipped = Vec::new();
+        let mut lines = Vec::new();
+        for (register, keys) in registers {
+            match keys::format_keys(keys) {
+                Some(keys) => lines.push(format!("{}:{}", register, keys)),
+                None => skipped.push(register.to_string()),
+            }
+        }
+
+        system::write_data_file(MACRO_FILE, &lines.join("\n"))?;
+
+        match skipped.is_empty() {
+            true => Ok(()),
+            false => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("unsupported keys in register {}", skipped.join(", ")),
+            )),
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recording.is_some()
+    }
+
+    pub fn recording_register(&self) -> Option<char> {
+        self.recording
+    }
+
+    // an uppercase register appends to its lowercase counterpart
+    pub fn start(&mut self, register: char) {
+        let lowercase = register.to_ascii_lowercase();
+
+        self.keys = match register.is_ascii_uppercase() {
+            true => self.registers.get(&lowercase).cloned().unwrap_or_default(),
+            false => Vec::new(),
+        };
+        self.recording = Some(lowercase);
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(register) = self.recording.take() {
+            self.registers
+                .insert(register, std::mem::take(&mut self.keys));
+        }
+    }
+
+    // keys fed by a running macro are not recorded again, the `@a` that
+    // started it already was
+    pub fn record(&mut self, key_event: KeyEvent) {
+        if self.recording.is_some() && self.playing == 0 {
+            self.keys.push(key_event);
+        }
+    }
+
+    // `@@` replays whichever register ran last
+    pub fn get(&mut self, register: char) -> Option<Vec<KeyEvent>> {
+        let register = match register {
+            '@' => self.last_played?,
+            register => register,
+        };
+
+        self.last_played = Some(register);
+        self.registers.get(&register).cloned()
+    }
+}
@@ -0,0 +1,172 @@
+use crate::utils::system;
+use std::collections::{BTreeMap, HashMap};
+
+const BOOKMARK_FILE: &str = "bookmarks";
+
+// names may hold the tabs and newlines the file is split on
+pub fn escape(field: &str) -> String {
+    field
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+}
+
+pub fn unescape(field: &str) -> String {
+    let mut unescaped = String::with_capacity(field.len());
+    let mut chars = field.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('t') => unescaped.push('\t'),
+            Some('n') => unescaped.push('\n'),
+            Some(c) => unescaped.push(c),
+            None => unescaped.push('\\'),
+        }
+    }
+
+    unescaped
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mark {
+    pub dir: String,
+    pub line: u16,
+    // the entry under the cursor, found again if the listing has changed
+    pub entry: String,
+}
+
+// m{a-z} marks an entry in the current directory, m{A-Z} remembers the
+// directory itself and works from anywhere. Uppercase marks are bookmarks
+// and are kept in the data directory, one `{mark}\t{line}\t{dir}\t{entry}`
+// per line, with tabs, newlines and backslashes in them escaped.
+#[derive(Debug, Default)]
+pub struct Marks {
+    local: HashMap<String, BTreeMap<char, String>>,
+    global: BTreeMap<char, Mark>,
+}
+
+impl Marks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load() -> Self {
+        let mut marks = Self::new();
+
+        for line in system::read_data_file(BOOKMARK_FILE)
+            .unwrap_or_default()
+            .lines()
+        {
+            let mut fields = line.splitn(4, '\t');
+            let (Some(mark), Some(number), Some(dir)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+
+            if let (Some(mark), Ok(number)) = (mark.chars().next(), number.parse()) {
+                marks.global.insert(
+                    mark,
+                    Mark {
+                        dir: unescape(dir),
+                        line: number,
+                        entry: unescape(fields.next().unwrap_or_default()),
+                    },
+                );
+            }
+        }
+
+        marks
+    }
+
+    pub fn save(&self) -> Result<(), std::io::Error> {
+        let content = self
+            .global
+            .iter()
+            .map(|(mark, found)| {
+                format!(
+                    "{}\t{}\t{}\t{}",
+                    mark,
+                    found.line,
+                    escape(&found.dir),
+                    escape(&found.entry)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        system::write_data_file(BOOKMARK_FILE, &content)
+    }
+
+    pub fn is_valid(mark: char) -> bool {
+        mark.is_ascii_alphabetic()
+    }
+
+    pub fn set(&mut self, mark: char, found: Mark) -> Result<(), std::io::Error> {
+        match mark.is_ascii_uppercase() {
+            true => {
+                self.global.insert(mark, found);
+                self.save()
+            }
+            false => {
+                self.local
+                    .entry(found.dir)
+                    .or_default()
+                    .insert(mark, found.entry);
+                Ok(())
+            }
+        }
+    }
+
+    // the entry a lowercase mark is on in `dir`
+    pub fn local(&self, dir: &str, mark: char) -> Option<&String> {
+        self.local.get(dir)?.get(&mark)
+    }
+
+    pub fn global(&self, mark: char) -> Option<&Mark> {
+        self.global.get(&mark)
+    }
+
+    pub fn delete(&mut self, dir: &str, mark: char) -> Result<bool, std::io::Error> {
+        match mark.is_ascii_uppercase() {
+            true if self.global.remove(&mark).is_some() => self.save().map(|_| true),
+            true => Ok(false),
+            false => Ok(self
+                .local
+                .get_mut(dir)
+                .is_some_and(|marks| marks.remove(&mark).is_some())),
+        }
+    }
+
+    // `:delmarks!` forgets the lowercase marks of a directory
+    pub fn clear_local(&mut self, dir: &str) {
+        self.local.remove(dir);
+    }
+
+    pub fn locals(&self, dir: &str) -> impl Iterator<Item = (&char, &String)> {
+        self.local.get(dir).into_iter().flatten()
+    }
+
+    pub fn globals(&self) -> impl Iterator<Item = (&char, &Mark)> {
+        self.global.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escaped_fields_hold_no_separators() {
+        let name = "tab\there\nnew\\line";
+        let escaped = escape(name);
+
+        assert!(!escaped.contains(['\t', '\n']));
+        assert_eq!(unescape(&escaped), name);
+    }
+}
@@ -1,9 +1,32 @@
 pub mod buffer_storage;
+pub mod change_recorder;
+pub mod clipboard;
+pub mod command_line;
+pub mod command_registry;
+pub mod commands;
 pub mod cursor;
+pub mod dual_pane;
+pub mod ex_command;
 pub mod file_helper;
+pub mod finder;
+pub mod frecency;
+pub mod global;
 pub mod input_buffer;
+pub mod jump_list;
+pub mod keys;
+pub mod macros;
+pub mod marks;
 pub mod motion_handler;
+pub mod options;
+pub mod popup;
 pub mod render_utils;
+pub mod search;
+pub mod shell;
+pub mod substitute;
 pub mod system;
-pub mod undo_stack;
+pub mod tabs;
+pub mod text_object;
+pub mod tree;
+pub mod undo_tree;
+pub mod word_motion;
 pub mod yank_buffer;
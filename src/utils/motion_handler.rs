@@ -2,10 +2,14 @@ pub mod handler {
     use crate::app::App;
     use crate::app::Mode;
     use crate::utils::buffer_storage::State;
+    use crate::utils::text_object::TextObject;
+    use crate::utils::tree;
+    use crate::utils::word_motion::WordMotion;
     use crate::utils::yank_buffer::YankType;
 
     pub fn dd(app: &mut App) {
-        let current_path: String = app.path.get_absolute_path().to_string();
+        let y = app.cursor.y as usize - 1;
+        let dir = app.line_dir(y);
 
         let line = {
             let buffer_content = &app.buffer_content;
@@ -16,37 +20,32 @@ pub mod handler {
                 .to_string()
         };
 
-        if app.cursor.y == app.buffer_content.lines().count().try_into().unwrap_or(0) {
-            app.delete_line_full(app.cursor.y - 1);
-            app.cursor.up();
+        // marks the file for deletion, an open directory takes its lines along
+        app.delete_lines(y, y);
+
+        if app.cursor.y as usize > app.get_line_count() {
+            app.cursor.y = app.get_line_count().max(1) as u16;
             app.cursor.reset_x();
-        } else {
-            app.delete_line_full(app.cursor.y - 1);
-            if app.cursor.x
-                > app
-                    .get_line_length(app.cursor.y - 1)
-                    .unwrap_or(1)
-                    .try_into()
-                    .unwrap_or(1)
-            {
-                app.cursor.x = app
-                    .get_line_length(app.cursor.y - 1)
-                    .unwrap_or(1)
-                    .try_into()
-                    .unwrap_or(1);
-            }
+        } else if app.cursor.x
+            > app
+                .get_line_length(app.cursor.y - 1)
+                .unwrap_or(1)
+                .try_into()
+                .unwrap_or(1)
+        {
+            app.cursor.x = app
+                .get_line_length(app.cursor.y - 1)
+                .unwrap_or(1)
+                .try_into()
+                .unwrap_or(1);
         }
 
-        app.yank_buffer.set_content(line.clone());
-
-        if let Some(mut view) = app.buffer_storage.get_view(&current_path) {
-            view.set_state(&line.trim(), State::Deleted);
-            app.buffer_storage.update_view(&current_path, view)
-        }
+        app.yank_buffer
+            .delete_from(line.clone(), YankType::Line, vec![dir]);
     }
 
     pub fn cc(app: &mut App) {
-        let current_path: String = app.path.get_absolute_path().to_string();
+        let dir = app.line_dir(app.cursor.y as usize - 1);
 
         let line = {
             let buffer_content = &app.buffer_content;
@@ -57,53 +56,27 @@ pub mod handler {
                 .to_string()
         };
 
+        // the indentation of a nested entry is kept
         app.delete_line(app.cursor.y - 1);
-        app.cursor.reset_x();
+        app.cursor.x = tree::indent(&line) as u16 + 1;
 
-        app.yank_buffer.set_content(line.clone());
+        app.yank_buffer
+            .delete_from(line.clone(), YankType::Line, vec![dir.clone()]);
 
         let _ = app.set_mode(Mode::Insert);
 
-        if let Some(mut view) = app.buffer_storage.get_view(&current_path) {
+        if let Some(mut view) = app.buffer_storage.get_view(&dir) {
             view.set_state(&line.trim(), State::Deleted);
-            app.buffer_storage.update_view(&current_path, view)
+            app.buffer_storage.update_view(&dir, view)
         }
     }
 
     pub fn dw(app: &mut App) {
-        let current_path: String = app.path.get_absolute_path().to_string();
-        let line = {
-            let buffer_content = &app.buffer_content;
-            buffer_content
-                .lines()
-                .nth(app.cursor.y as usize - 1)
-                .unwrap_or("")
-                .to_string()
-        };
-
-        app.delete_word(app.cursor.x - 1, app.cursor.y - 1);
-
-        if app.cursor.x
-            >= app
-                .get_line_length(app.cursor.y - 1)
-                .unwrap_or(0)
-                .try_into()
-                .unwrap_or(0)
-        {
-            app.cursor.left();
-        }
-
-        app.yank_buffer.set_content(line.clone());
-
-        if let Some(mut view) = app.buffer_storage.get_view(&current_path) {
-            view.set_state(&line.trim(), State::Deleted);
-            app.buffer_storage.update_view(&current_path, view)
-        }
+        word_operator(app, 'd', 'w', 1);
     }
 
     pub fn cw(app: &mut App) {
-        app.delete_word(app.cursor.x - 1, app.cursor.y - 1);
-        let _ = app.set_mode(Mode::Insert);
+        word_operator(app, 'c', 'w', 1);
     }
 
     pub fn i(app: &mut App) {
@@ -135,10 +108,17 @@ pub mod handler {
     }
 
     pub fn o(app: &mut App) {
+        // in tree mode the new line starts in the branch it is opened in
+        let indentation = app.indentation_at(app.cursor.y as usize);
+
         app.move_max_x();
-        app.insert_at(app.cursor.x, app.cursor.y - 1, "\n "); // whitespace is needed to actually start a new line, do not remove!!!
+        app.insert_at(
+            app.cursor.x,
+            app.cursor.y - 1,
+            &format!("\n{} ", indentation),
+        ); // whitespace is needed to actually start a new line, do not remove!!!
         app.cursor.down();
-        app.cursor.reset_x();
+        app.cursor.x = indentation.len() as u16 + 1;
         let _ = app.set_mode(Mode::Insert);
     }
 
@@ -147,8 +127,10 @@ pub mod handler {
         if app.cursor.y == 1 {
             app.buffer_content = String::from("\n") + &app.buffer_content;
         } else {
-            app.insert_at(0, app.cursor.y - 1, "\n");
-            app.cursor.reset_x();
+            let indentation = app.indentation_at(app.cursor.y as usize - 1);
+
+            app.insert_at(0, app.cursor.y - 1, &format!("{}\n", indentation));
+            app.cursor.x = indentation.len() as u16 + 1;
         }
 
         let _ = app.set_mode(Mode::Insert);
@@ -215,47 +197,242 @@ pub mod handler {
         app.cursor.x = 1;
     }
 
-    pub fn w(app: &mut App) {
-        let line = app
-            .buffer_content
-            .lines()
-            .nth(app.cursor.y as usize - 1)
-            .unwrap_or("");
-        let x = app.cursor.x as usize;
-        let new_x = app.get_end_x(&line.to_string(), x, true);
+    // moves the cursor to a line given by the view, like vim's H, M and L
+    // and the scroll commands it lands on the first column
+    fn jump_to_line(app: &mut App, y: u16) {
+        let lines = app.get_line_count() as u16;
 
-        if new_x - 1 == app.get_line_length(app.cursor.y - 1).unwrap_or(0)
-            && (app.cursor.y.try_into().unwrap_or(0) < app.get_line_count())
-        {
-            app.cursor.down();
-            app.cursor.reset_x();
-        } else {
-            app.cursor.move_word(line, new_x);
+        app.cursor.y = y.clamp(1, lines.max(1));
+        app.cursor.reset_x();
+    }
+
+    fn margin(app: &App) -> u16 {
+        app.options
+            .scroll_off
+            .min(app.cursor.height.saturating_sub(1) / 2)
+    }
+
+    #[allow(non_snake_case)]
+    pub fn H(app: &mut App) {
+        let (first, _) = app.cursor.visible_lines(app.get_line_count() as u16);
+
+        match first == 1 {
+            true => jump_to_line(app, first),
+            false => jump_to_line(app, first + margin(app)),
+        }
+    }
+
+    #[allow(non_snake_case)]
+    pub fn M(app: &mut App) {
+        let (first, last) = app.cursor.visible_lines(app.get_line_count() as u16);
+
+        jump_to_line(app, first + (last - first) / 2);
+    }
+
+    #[allow(non_snake_case)]
+    pub fn L(app: &mut App) {
+        let lines = app.get_line_count() as u16;
+        let (_, last) = app.cursor.visible_lines(lines);
+
+        match last == lines {
+            true => jump_to_line(app, last),
+            false => jump_to_line(app, last.saturating_sub(margin(app))),
+        }
+    }
+
+    // zt, zz and zb put the cursor line at the top, middle or bottom
+    pub fn zt(app: &mut App) {
+        app.cursor.scroll_to_row(1 + margin(app));
+    }
+
+    pub fn zz(app: &mut App) {
+        app.cursor.scroll_to_row(app.cursor.height.div_ceil(2));
+    }
+
+    pub fn zb(app: &mut App) {
+        let row = app.cursor.height.saturating_sub(margin(app));
+        app.cursor.scroll_to_row(row);
+    }
+
+    // Ctrl-d and Ctrl-u move the view and the cursor by half a page
+    fn scroll_half_page(app: &mut App, down: bool) {
+        let lines = app.get_line_count() as u16;
+        let amount = (app.cursor.height / 2).max(1);
+
+        let (scroll, y) = match down {
+            true => (app.cursor.scroll + amount, app.cursor.y + amount),
+            false => (
+                app.cursor.scroll.saturating_sub(amount),
+                app.cursor.y.saturating_sub(amount),
+            ),
+        };
+
+        app.cursor.scroll = scroll.min(lines.saturating_sub(app.cursor.height));
+        jump_to_line(app, y);
+    }
+
+    pub fn ctrl_d(app: &mut App) {
+        scroll_half_page(app, true);
+    }
+
+    pub fn ctrl_u(app: &mut App) {
+        scroll_half_page(app, false);
+    }
+
+    // Ctrl-f and Ctrl-b turn a page, keeping two lines of the last one
+    fn scroll_page(app: &mut App, forward: bool) {
+        let lines = app.get_line_count() as u16;
+        let amount = app.cursor.height.saturating_sub(2).max(1);
+
+        app.cursor.scroll = match forward {
+            true => (app.cursor.scroll + amount).min(lines.saturating_sub(app.cursor.height)),
+            false => app.cursor.scroll.saturating_sub(amount),
+        };
+
+        let (first, last) = app.cursor.visible_lines(lines);
+        let top = first + margin(app);
+        let bottom = last.saturating_sub(margin(app)).max(top);
+        jump_to_line(app, app.cursor.y.clamp(top, bottom));
+    }
+
+    pub fn ctrl_f(app: &mut App) {
+        scroll_page(app, true);
+    }
+
+    pub fn ctrl_b(app: &mut App) {
+        scroll_page(app, false);
+    }
+
+    // `cw` looks for the end of the word from the char before the cursor, so
+    // on the last char of a word it only changes that char like in vim
+    fn word_target(app: &App, motion: char, count: usize, before: bool) -> (usize, usize) {
+        let word_motion = WordMotion::new(&app.buffer_content, motion.is_uppercase());
+
+        let mut index =
+            word_motion.to_index(app.cursor.y as usize - 1, app.cursor.x.max(1) as usize - 1);
+        if before {
+            index = index.saturating_sub(1);
+        }
+        for _ in 0..count.max(1) {
+            index = word_motion.apply(motion, index);
         }
+
+        word_motion.to_position(index)
+    }
+
+    fn move_word(app: &mut App, motion: char, count: usize) {
+        let (row, col) = word_target(app, motion, count, false);
+
+        // `w` past the last word stops on the last character
+        let length = app.get_line_length(row as u16).unwrap_or(0);
+        let col = col.min(length.max(1) - 1);
+
+        app.cursor.y = row as u16 + 1;
+        app.cursor.x = col as u16 + 1;
+    }
+
+    pub fn w(app: &mut App) {
+        move_word(app, 'w', 1);
+    }
+
+    #[allow(non_snake_case)]
+    pub fn W(app: &mut App) {
+        move_word(app, 'W', 1);
     }
 
     pub fn b(app: &mut App) {
+        move_word(app, 'b', 1);
+    }
+
+    #[allow(non_snake_case)]
+    pub fn B(app: &mut App) {
+        move_word(app, 'B', 1);
+    }
+
+    pub fn e(app: &mut App) {
+        move_word(app, 'e', 1);
+    }
+
+    #[allow(non_snake_case)]
+    pub fn E(app: &mut App) {
+        move_word(app, 'E', 1);
+    }
+
+    // operators never leave the current line since every line is a file name,
+    // a motion that lands on another line covers the rest of this one
+    pub fn word_operator(app: &mut App, operator: char, motion: char, count: usize) {
+        let row = app.cursor.y as usize - 1;
         let line = app
             .buffer_content
             .lines()
-            .nth(app.cursor.y as usize - 1)
-            .unwrap_or("");
-        let x = app.cursor.x as usize;
+            .nth(row)
+            .unwrap_or("")
+            .to_string();
+        let length = line.chars().count();
 
-        if x <= 1 && app.cursor.y > 1 {
-            app.cursor.up();
-            app.cursor.x = app
-                .get_line_length(app.cursor.y - 1)
-                .unwrap_or(1)
-                .try_into()
-                .unwrap_or(1);
-        } else {
-            let new_x = app.get_start_x(&line.to_string(), x);
-            app.cursor.move_word(line, new_x);
+        if length == 0 {
+            return;
+        }
+
+        let col = (app.cursor.x.max(1) as usize - 1).min(length - 1);
+        let on_word = !line.chars().nth(col).unwrap_or(' ').is_whitespace();
+
+        // `cw` on a word changes to its end, just like vim does
+        let (motion, before) = match (operator, motion) {
+            ('c', 'w') if on_word => ('e', true),
+            ('c', 'W') if on_word => ('E', true),
+            _ => (motion, false),
+        };
+
+        let (target_row, target_col) = word_target(app, motion, count, before);
+
+        let (start, end) = match motion {
+            'b' | 'B' if target_row < row => (0, col),
+            'b' | 'B' => (target_col, col),
+            'e' | 'E' if target_row > row => (col, length),
+            'e' | 'E' => (col, target_col + 1),
+            _ if target_row > row => (col, length),
+            _ => (col, target_col.max(col)),
+        };
+
+        operate_range(app, operator, &line, start, end);
+    }
+
+    fn parse_word_command(command: &str) -> Option<(usize, Option<char>, char)> {
+        let digits: String = command.chars().take_while(|c| c.is_ascii_digit()).collect();
+        let rest: Vec<char> = command[digits.len()..].chars().collect();
+        let count = digits.parse().unwrap_or(1);
+
+        match rest.as_slice() {
+            [motion] if "wWeEbB".contains(*motion) => Some((count, None, *motion)),
+            [operator, motion] if "cdy".contains(*operator) && "wWeEbB".contains(*motion) => {
+                Some((count, Some(*operator), *motion))
+            }
+            _ => None,
         }
     }
 
+    // handles `[count][operator]{w,W,e,E,b,B}`, returns false for anything else
+    pub fn word_command(app: &mut App, command: &str) -> bool {
+        match parse_word_command(command) {
+            Some((count, Some(operator), motion)) => word_operator(app, operator, motion, count),
+            Some((count, None, motion)) => move_word(app, motion, count),
+            None => return false,
+        }
+
+        true
+    }
+
     pub fn x(app: &mut App) {
+        let deleted = app
+            .buffer_content
+            .lines()
+            .nth(app.cursor.y as usize - 1)
+            .and_then(|line| line.chars().nth(app.cursor.x.max(1) as usize - 1));
+
+        if let Some(deleted) = deleted {
+            app.yank_buffer.delete(deleted.to_string(), YankType::Char);
+        }
         app.delete_at(app.cursor.x - 1, app.cursor.y - 1);
 
         if app.cursor.x
@@ -269,6 +446,32 @@ pub mod handler {
         }
     }
 
+    pub fn tilde(app: &mut App) {
+        let line = app
+            .buffer_content
+            .lines()
+            .nth(app.cursor.y as usize - 1)
+            .unwrap_or("")
+            .to_string();
+        let x = app.cursor.x.max(1) as usize - 1;
+
+        let toggled: String = line
+            .chars()
+            .enumerate()
+            .map(|(index, c)| match index == x {
+                true if c.is_uppercase() => c.to_lowercase().collect::<String>(),
+                true => c.to_uppercase().collect::<String>(),
+                false => c.to_string(),
+            })
+            .collect();
+
+        if toggled != line {
+            app.replace_line(app.cursor.y - 1, &toggled);
+        }
+
+        l(app);
+    }
+
     pub fn s(app: &mut App) {
         x(app);
         let _ = app.set_mode(Mode::Insert);
@@ -296,42 +499,29 @@ pub mod handler {
         dd(app);
     }
 
-    pub fn diw(app: &mut App) {
-        let current_path: String = app.path.get_absolute_path().to_string();
-
-        let line = {
-            let buffer_content = &app.buffer_content;
-            buffer_content
-                .lines()
-                .nth(app.cursor.y as usize - 1)
-                .unwrap_or("")
-                .to_string()
-        };
+    fn inner_word(app: &App) -> (String, usize, usize) {
+        let line = app
+            .buffer_content
+            .lines()
+            .nth(app.cursor.y as usize - 1)
+            .unwrap_or("")
+            .to_string();
 
         let x = app.cursor.x as usize;
-        let start_index = app.seek_special_character_backward(&line.to_string(), x);
-        let end_index = app.seek_special_character_forward(&line.to_string(), x);
-
-        app.delete_range(
-            start_index.try_into().unwrap_or(0),
-            app.cursor.y - 1,
-            end_index - start_index,
-        );
+        let start_index = app.seek_special_character_backward(&line, x);
+        let end_index = app.seek_special_character_forward(&line, x);
 
-        app.cursor.x = start_index.try_into().unwrap_or(1);
-        app.cursor.right(0);
-
-        app.yank_buffer.set_content(line.to_string());
+        (line, start_index, end_index)
+    }
 
-        if let Some(mut view) = app.buffer_storage.get_view(&current_path) {
-            view.set_state(&line.trim(), State::Deleted);
-            app.buffer_storage.update_view(&current_path, view)
-        }
+    pub fn diw(app: &mut App) {
+        let (line, start, end) = inner_word(app);
+        operate_range(app, 'd', &line, start, end);
     }
 
     pub fn ciw(app: &mut App) {
-        diw(app);
-        let _ = app.set_mode(Mode::Insert);
+        let (line, start, end) = inner_word(app);
+        operate_range(app, 'c', &line, start, end);
     }
 
     pub fn u(app: &mut App) {
@@ -345,86 +535,156 @@ pub mod handler {
             .nth(app.cursor.y as usize - 1)
             .unwrap_or("");
 
-        app.yank_buffer.set_content(line.to_string());
-        app.yank_buffer.set_yank_type(YankType::Line);
+        let dir = app.line_dir(app.cursor.y as usize - 1);
+        app.yank_buffer
+            .yank_from(line.to_string(), YankType::Line, vec![dir]);
+    }
+
+    // yanks the absolute path of the hovered entry, to the clipboard unless
+    // another register was picked
+    pub fn gy(app: &mut App) {
+        let current_path = app.path.get_absolute_path();
+
+        let path = match app.get_hovered_filename().as_str() {
+            "../" => app
+                .path
+                .get_parent()
+                .map(|parent| parent.get_absolute_path())
+                .unwrap_or(current_path),
+            name => {
+                let dir = app.line_dir(app.cursor.y as usize - 1);
+                format!("{}/{}", dir.trim_end_matches('/'), name)
+            }
+        };
+
+        if app.yank_buffer.selected().is_none() {
+            app.yank_buffer.select('+');
+        }
+        app.yank_buffer.yank(path, YankType::Char);
     }
 
     pub fn yiw(app: &mut App) {
+        let (line, start, end) = inner_word(app);
+        operate_range(app, 'y', &line, start, end);
+    }
+
+    // handles `[cdy][ai]{object}` for every object that isn't `iw`, see
+    // text_object.rs for the filename specific ones
+    pub fn text_object(app: &mut App, command: &str) {
+        let chars: Vec<char> = command.chars().collect();
+        if chars.len() != 3 || !"cdy".contains(chars[0]) || !"ai".contains(chars[1]) {
+            return;
+        }
+
+        let (operator, around) = (chars[0], chars[1] == 'a');
+        let object = match TextObject::from_char(chars[2]) {
+            Some(object) => object,
+            None => return,
+        };
+
         let line = app
             .buffer_content
             .lines()
             .nth(app.cursor.y as usize - 1)
-            .unwrap_or("");
+            .unwrap_or("")
+            .to_string();
 
-        let x = app.cursor.x as usize;
-        let start_index = app.seek_special_character_backward(&line.to_string(), x);
-        let end_index = app.seek_special_character_forward(&line.to_string(), x);
+        let x = (app.cursor.x.max(1) - 1) as usize;
+        let (start, end) = match object.find(&line, x, around) {
+            Some(range) => range,
+            None => return,
+        };
+
+        operate_range(app, operator, &line, start, end);
+    }
+
+    // yanks [start, end) of the current line and removes it unless the
+    // operator is `y`, `c` leaves the cursor in insert mode
+    fn operate_range(app: &mut App, operator: char, line: &str, start: usize, end: usize) {
+        if start >= end {
+            return;
+        }
+
+        let content: String = line.chars().skip(start).take(end - start).collect();
+        match operator {
+            'y' => app.yank_buffer.yank(content, YankType::Char),
+            _ => app.yank_buffer.delete(content, YankType::Char),
+        }
 
-        let content = &line[start_index..end_index];
-        app.yank_buffer.set_content(content.to_string());
-        app.yank_buffer.set_yank_type(YankType::Word);
+        if operator != 'y' {
+            let new_line: String = line
+                .chars()
+                .take(start)
+                .chain(line.chars().skip(end))
+                .collect();
 
-        app.cursor.x = start_index.try_into().unwrap_or(1).max(1);
+            app.replace_line(app.cursor.y - 1, &new_line);
+        }
+
+        app.cursor.x = start as u16 + 1;
+
+        match operator {
+            'c' => {
+                let _ = app.set_mode(Mode::Insert);
+            }
+            _ => {
+                let length = app.get_line_length(app.cursor.y - 1).unwrap_or(1).max(1);
+                app.cursor.x = app.cursor.x.min(length as u16);
+            }
+        }
     }
 
-    // TODO implement Paste - Move file
-    pub fn p(app: &mut App) {
-        let current_path: String = app.path.get_absolute_path().to_string();
+    // lines yanked in another directory move or copy their entries here
+    fn paste_lines(app: &mut App, y: u16, content: &str, dirs: &[String]) {
+        let mut y = y;
 
-        let buffer_content = app.buffer_content.clone();
+        for (index, line) in content.lines().enumerate() {
+            match app.paste_line(y, line, dirs.get(index).map(String::as_str)) {
+                Ok(()) => y += 1,
+                Err(error) => app.echo_error(&error),
+            }
+        }
+    }
 
-        let content = app.yank_buffer.content.clone();
-        let yank_type = app.yank_buffer.get_yank_type();
+    pub fn p(app: &mut App) {
+        let register = match app.yank_buffer.get() {
+            Some(register) => register,
+            None => return,
+        };
 
-        match yank_type {
+        match register.yank_type {
             YankType::Line => {
-                app.move_max_x();
-                app.insert_at(app.cursor.x, app.cursor.y - 1, &format!("\n{}", &content));
-                app.cursor.down();
+                paste_lines(app, app.cursor.y, &register.content, &register.dirs);
 
+                app.cursor.down();
                 app.cursor.reset_x();
-
-                if let Some(mut view) = app.buffer_storage.get_view(&current_path) {
-                    view.set_state(&content.trim(), State::Moved);
-                    view.set_path(&content, &current_path);
-                    app.buffer_storage.update_view(&current_path, view)
-                }
             }
             YankType::Char | YankType::Word => {
-                app.insert_at(app.cursor.x - 1, app.cursor.y - 1, &content);
-                app.cursor.x += content.len() as u16;
+                app.insert_at(app.cursor.x - 1, app.cursor.y - 1, &register.content);
+                app.cursor.x += register.content.chars().count() as u16;
             }
         }
-
-        app.undo_stack
-            .push(buffer_content, app.cursor.x.into(), app.cursor.y.into());
     }
 
     #[allow(non_snake_case)]
     pub fn P(app: &mut App) {
-        let buffer_content = app.buffer_content.clone();
+        let register = match app.yank_buffer.get() {
+            Some(register) => register,
+            None => return,
+        };
 
-        let content = app.yank_buffer.content.clone();
-        let yank_type = app.yank_buffer.get_yank_type();
-
-        match yank_type {
-            YankType::Line => {
-                app.cursor.reset_x();
-                app.insert_at(
-                    app.cursor.x - 1,
-                    app.cursor.y - 1,
-                    &format!("{}\n", &content),
-                );
+        match register.yank_type {
+            // nothing goes above '../'
+            YankType::Line if app.cursor.y > 1 => {
+                paste_lines(app, app.cursor.y - 1, &register.content, &register.dirs);
 
                 app.cursor.reset_x();
             }
+            YankType::Line => {}
             YankType::Char | YankType::Word => {
-                app.insert_at(app.cursor.x - 1, app.cursor.y - 1, &content);
-                app.cursor.x += content.len() as u16;
+                app.insert_at(app.cursor.x - 1, app.cursor.y - 1, &register.content);
+                app.cursor.x += register.content.chars().count() as u16;
             }
         }
-
-        app.undo_stack
-            .push(buffer_content, app.cursor.x.into(), app.cursor.y.into());
     }
 }
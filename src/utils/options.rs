@@ -0,0 +1,93 @@
+use crate::utils::system;
+
+// User options, read from $XDG_CONFIG_HOME/foil/config. Every line is an
+// assignment in the same form `:set` takes: `name`, `noname` or `name=value`.
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub persist_macros: bool,
+    pub ignore_case: bool,
+    pub smart_case: bool,
+    pub scroll_off: u16,
+    // h and l leave the line for the parent or the hovered directory
+    pub miller: bool,
+    // directories open in place with zo, zc and za
+    pub tree: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            persist_macros: false,
+            ignore_case: true,
+            smart_case: true,
+            scroll_off: 0,
+            miller: false,
+            tree: false,
+        }
+    }
+}
+
+impl Options {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load() -> Self {
+        let mut options = Self::new();
+
+        let config = std::fs::read_to_string(system::config_dir().join("config"));
+        for line in config.unwrap_or_default().lines() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let _ = options.set(line.trim_start_matches("set "));
+        }
+
+        options
+    }
+
+    pub fn set(&mut self, assignment: &str) -> Result<(), String> {
+        let (name, value) = match assignment.split_once('=') {
+            Some((name, value)) => (name.trim(), Some(value.trim())),
+            None => (assignment.trim(), None),
+        };
+
+        let (name, enabled) = match name.strip_prefix("no") {
+            Some(name) if value.is_none() => (name, false),
+            _ => (name, true),
+        };
+
+        match name {
+            "persistmacros" => self.persist_macros = parse_bool(value, enabled)?,
+            "ignorecase" | "ic" => self.ignore_case = parse_bool(value, enabled)?,
+            "smartcase" | "scs" => self.smart_case = parse_bool(value, enabled)?,
+            "scrolloff" | "so" => self.scroll_off = parse_number(value)?,
+            "miller" => self.miller = parse_bool(value, enabled)?,
+            "tree" => self.tree = parse_bool(value, enabled)?,
+            _ => return Err(format!("Unknown option: {}", name)),
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_bool(value: Option<&str>, enabled: bool) -> Result<bool, String> {
+    match value {
+        None => Ok(enabled),
+        Some("true") | Some("1") => Ok(true),
+        Some("false") | Some("0") => Ok(false),
+        Some(value) => Err(format!("Invalid boolean: {}", value)),
+    }
+}
+
+fn parse_number(value: Option<&str>) -> Result<u16, String> {
+    match value {
+        Some(value) => value
+            .parse()
+            .map_err(|_| format!("Invalid number: {}", value)),
+        None => Err(String::from("Number required")),
+    }
+}
@@ -0,0 +1,71 @@
+use crate::app::App;
+
+// Text shown over the panes. j/k and friends scroll, any other key closes
+// it. Lists that allow deleting have a selected line instead, d or x hands
+// it to `on_delete`
+#[derive(Debug, Clone)]
+pub struct Popup {
+    pub title: String,
+    pub lines: Vec<String>,
+    pub scroll: u16,
+    pub selected: Option<usize>,
+    pub on_delete: Option<fn(&mut App, &str)>,
+}
+
+impl Popup {
+    pub fn new(title: &str, lines: Vec<String>) -> Self {
+        Self {
+            title: format!(" {} ", title),
+            lines,
+            scroll: 0,
+            selected: None,
+            on_delete: None,
+        }
+    }
+
+    pub fn with_delete(mut self, on_delete: fn(&mut App, &str)) -> Self {
+        self.selected = Some(0);
+        self.on_delete = Some(on_delete);
+        self
+    }
+
+    fn last_line(&self) -> u16 {
+        self.lines.len().saturating_sub(1) as u16
+    }
+
+    pub fn selected_line(&self) -> Option<&String> {
+        self.lines.get(self.selected?)
+    }
+
+    pub fn select(&mut self, index: usize) {
+        if self.selected.is_some() {
+            self.selected = Some(index.min(self.last_line() as usize));
+        }
+    }
+
+    pub fn scroll_down(&mut self, amount: u16) {
+        match self.selected {
+            Some(selected) => self.select(selected + amount as usize),
+            None => self.scroll = self.scroll.saturating_add(amount).min(self.last_line()),
+        }
+    }
+
+    pub fn scroll_up(&mut self, amount: u16) {
+        match self.selected {
+            Some(selected) => self.select(selected.saturating_sub(amount as usize)),
+            None => self.scroll = self.scroll.saturating_sub(amount),
+        }
+    }
+
+    pub fn scroll_to_start(&mut self) {
+        self.select(0);
+        self.scroll = 0;
+    }
+
+    pub fn scroll_to_end(&mut self) {
+        self.select(self.last_line() as usize);
+        if self.selected.is_none() {
+            self.scroll = self.last_line();
+        }
+    }
+}
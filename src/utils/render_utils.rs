@@ -3,7 +3,10 @@ use crate::{
     file_helper::PathHelper,
     utils::{
         buffer_storage::{FileType, State},
-        system,
+        finder::Finder,
+        popup::Popup,
+        search, substitute, system,
+        tabs::Tab,
     },
 };
 use ratatui::{
@@ -25,23 +28,58 @@ pub struct BodyLayout<'a> {
 }
 
 pub fn get_header<'a>(block: &Block<'a>, app: &App) -> Paragraph<'a> {
-    let spans = Line::from(vec![
+    let mut spans = vec![
         Span::styled(get_hostname(), Style::default().fg(Color::Yellow)),
         Span::styled(
             format!(" {}", get_dirname(app)),
             Style::default().fg(Color::Blue),
         ),
-    ]);
+    ];
 
-    Paragraph::new(spans).block(block.clone())
+    // the tab line only shows up once there is more than one
+    if app.tabs.count() > 1 {
+        spans.push(Span::raw("  "));
+
+        let paths = app.tabs.paths(&app.path.get_absolute_path());
+        for (index, path) in paths.iter().enumerate() {
+            let name = match path.trim_end_matches('/').rsplit('/').next() {
+                Some(name) if !name.is_empty() => name,
+                _ => "/",
+            };
+
+            let style = match index == app.tabs.current {
+                true => Style::default().bg(Color::Blue).fg(Color::Black),
+                false => Style::default().fg(Color::DarkGray),
+            };
+
+            spans.push(Span::styled(format!(" {}:{} ", index + 1, name), style));
+        }
+    }
+
+    Paragraph::new(Line::from(spans)).block(block.clone())
 }
 
 pub fn get_footer<'a>(block: &Block<'a>, app: &App) -> Paragraph<'a> {
     let spans: Line = match app.mode {
-        Mode::Command => Line::from(vec![Span::raw(format!(
-            ":{}",
-            app.command.clone().unwrap_or("".to_string())
+        Mode::Command => Line::from(vec![Span::raw(format!(":{}", app.command_line.content))]),
+        Mode::Search => Line::from(vec![Span::raw(format!(
+            "{}{}",
+            app.search.prompt(),
+            app.search.line.content
         ))]),
+        _ if app.message.is_some() => {
+            let message = app.message.as_ref().unwrap();
+            let color = if message.error {
+                Color::Red
+            } else {
+                Color::White
+            };
+
+            Line::from(vec![Span::styled(
+                message.text.clone(),
+                Style::default().fg(color),
+            )])
+        }
         _ => Line::from(vec![
             Span::styled(format!("{}", app.mode), Style::default().fg(Color::White)),
             Span::styled(
@@ -52,6 +90,13 @@ pub fn get_footer<'a>(block: &Block<'a>, app: &App) -> Paragraph<'a> {
                 format!(" {}        ", get_current_file_size(app)),
                 Style::default().fg(Color::Green),
             ),
+            Span::styled(
+                match app.macros.recording_register() {
+                    Some(register) => format!("recording @{}", register),
+                    None => String::new(),
+                },
+                Style::default().fg(Color::Yellow),
+            ),
         ]),
     };
 
@@ -65,31 +110,25 @@ pub fn get_body<'a>(app: &'a mut App) -> BodyLayout<'a> {
     let dir_parts = cl.split("/").collect::<Vec<_>>();
     let current_folder = dir_parts[dir_parts.len() - 1].to_string();
 
-    let mut current_files = current_dir
-        .get_dir_names_printable(true)
-        .unwrap_or(vec![])
-        .iter()
-        .map(|s| PathHelper::trim_path(s.as_str()))
-        .collect::<Vec<_>>();
-
-    current_files.sort_by(|a: &String, b: &String| {
-        let file_type_a = app.get_file_type(&current_dir.get_absolute_path(), a);
-        let file_type_b = app.get_file_type(&current_dir.get_absolute_path(), b);
-
-        match (file_type_a, file_type_b) {
-            (FileType::Directory, FileType::File) => std::cmp::Ordering::Less,
-            (FileType::File, FileType::Directory) => std::cmp::Ordering::Greater,
-            _ => a.cmp(b),
-        }
-    });
-
-    current_files.insert(0, String::from("../"));
+    let current_files = app.listing(&current_dir);
 
     if app.rerender_dir_content {
         app.buffer_content = current_files.join("\n");
         app.rerender_dir_content = false;
+
+        app.cursor.y = app.cursor.y.min(current_files.len() as u16);
     }
 
+    if let Some(target) = app.cursor_target.take() {
+        if let Some(index) = current_files.iter().position(|file| *file == target) {
+            app.cursor.y = index as u16 + 1;
+            app.cursor.reset_x();
+        }
+    }
+
+    app.cursor
+        .scroll_into_view(app.options.scroll_off, current_files.len() as u16);
+
     let mut parent_dir: PathHelper = current_dir.clone();
     let _ = parent_dir.cd("..");
     let mut parent_files: Vec<String> = parent_dir
@@ -110,6 +149,13 @@ pub fn get_body<'a>(app: &'a mut App) -> BodyLayout<'a> {
         }
     });
 
+    // the directory we are in stays visible in the parent pane
+    let parent_scroll = parent_files
+        .iter()
+        .position(|line| *line == current_folder)
+        .map(|index| follow(index as u16, app.cursor.height))
+        .unwrap_or(0);
+
     let left = Paragraph::new(
         parent_files
             .iter()
@@ -124,6 +170,7 @@ pub fn get_body<'a>(app: &'a mut App) -> BodyLayout<'a> {
             })
             .collect::<Vec<_>>(),
     )
+    .scroll((parent_scroll, 0))
     .block(
         Block::default()
             .title(parent_dir.get_absolute_path())
@@ -138,20 +185,70 @@ pub fn get_body<'a>(app: &'a mut App) -> BodyLayout<'a> {
         .dir;
 
     let hovered_file = app.get_hovered_filename();
+    let selection = app.visual_selection();
 
-    let middle = Paragraph::new(
-        app.buffer_content
-            .lines()
-            .map(|line| {
-                let (bg, fg) = get_line_colors(app, current_view, line, &hovered_file);
+    // in tree mode the hovered entry may be in a directory further down
+    let mut hovered_dir = current_dir.clone();
+    let _ = hovered_dir.set_path(&app.line_dir(app.cursor.y.max(1) as usize - 1));
 
-                Line::from(Span::styled(
-                    format!("{:<width$}", line, width = u16::MAX as usize),
-                    Style::default().bg(bg).fg(fg),
-                ))
+    // a substitute that is still being typed shows its result right away
+    let preview = match app.mode {
+        Mode::Command => substitute::preview(app, &app.command_line.content).unwrap_or_default(),
+        _ => vec![],
+    };
+
+    // matches of the search being typed, or of the last one until :noh
+    let highlight = match app.mode {
+        Mode::Search => app.search_regex(&app.search.line.content),
+        _ if app.search.highlight => app
+            .search
+            .pattern
+            .as_ref()
+            .and_then(|pattern| app.search_regex(pattern)),
+        _ => None,
+    };
+
+    let lines: Vec<&str> = app.buffer_content.lines().collect();
+    let middle = Paragraph::new(
+        lines
+            .iter()
+            .enumerate()
+            .map(|(index, line)| {
+                // nested entries are found in their own view, and a name can
+                // be there more than once so only the hovered line is lit
+                let view = match app.options.tree {
+                    true => app.dir_of(&lines, index),
+                    false => current_view.clone(),
+                };
+                let hovered = match index + 1 == app.cursor.y as usize {
+                    true => hovered_file.as_str(),
+                    false => "",
+                };
+                let (mut bg, mut fg) = get_line_colors(app, &view, line.trim(), hovered);
+
+                if selection.is_some_and(|(start, end)| (start..=end).contains(&(index + 1))) {
+                    (bg, fg) = (Color::DarkGray, Color::White);
+                }
+
+                let line = match preview.iter().find(|(number, _)| *number == index + 1) {
+                    Some((_, replaced)) => {
+                        fg = Color::Yellow;
+                        replaced.as_str()
+                    }
+                    None => line,
+                };
+
+                let style = Style::default().bg(bg).fg(fg);
+                let matches = highlight
+                    .as_ref()
+                    .map(|regex| search::matches(line, regex))
+                    .unwrap_or_default();
+
+                highlight_matches(line, &matches, style)
             })
             .collect::<Vec<_>>(),
     )
+    .scroll((app.cursor.scroll, 0))
     .block(
         Block::default()
             .title("Current Directory")
@@ -159,11 +256,25 @@ pub fn get_body<'a>(app: &'a mut App) -> BodyLayout<'a> {
             .border_type(BorderType::Rounded),
     );
 
+    // a directory that was visited before is previewed around the entry the
+    // cursor was left on, the preview has no "../" line
+    let mut child_dir = hovered_dir.clone();
+    let _ = child_dir.cd(&hovered_file);
+    let child_scroll = match hovered_file.as_str() {
+        "../" => 0,
+        _ => app
+            .positions
+            .get(&child_dir.get_absolute_path())
+            .map(|position| follow(position.y.saturating_sub(2), app.cursor.height))
+            .unwrap_or(0),
+    };
+
     let right = Paragraph::new(get_file_preview_content(
         app,
         hovered_file.clone(),
-        current_dir.clone(),
+        hovered_dir,
     ))
+    .scroll((child_scroll, 0))
     .block(
         Block::default()
             .title(if hovered_file == "../" {
@@ -182,6 +293,60 @@ pub fn get_body<'a>(app: &'a mut App) -> BodyLayout<'a> {
     }
 }
 
+// the listing of the unfocused side in dual-pane mode, the entry its cursor
+// was left on stays highlighted
+pub fn get_other_pane<'a>(app: &App, tab: &Tab, height: u16) -> Paragraph<'a> {
+    let dir = tab.path.get_absolute_path();
+    let (_, y, scroll) = tab.cursor;
+
+    let hovered = tab
+        .buffer_content
+        .lines()
+        .nth(y.max(1) as usize - 1)
+        .unwrap_or_default()
+        .to_string();
+
+    // the pane may have been smaller or larger while it had the focus
+    let scroll = scroll
+        .min(y.saturating_sub(1))
+        .max(y.saturating_sub(height));
+
+    let lines = tab
+        .buffer_content
+        .lines()
+        .map(|line| {
+            let (bg, fg) = get_line_colors(app, &dir, line, &hovered);
+            Line::styled(line.to_string(), Style::default().bg(bg).fg(fg))
+        })
+        .collect::<Vec<_>>();
+
+    Paragraph::new(lines)
+        .scroll((scroll, 0))
+        .block(get_pane_block(dir, false))
+}
+
+pub fn get_pane_block<'a>(title: String, focused: bool) -> Block<'a> {
+    let border = match focused {
+        true => Style::default(),
+        false => Style::default().fg(Color::DarkGray),
+    };
+
+    Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(border)
+}
+
+// scroll offset that keeps the given 0-based line on screen, near the middle
+// once the pane is too short to show it from the top
+fn follow(index: u16, height: u16) -> u16 {
+    match index < height {
+        true => 0,
+        false => index - height / 2,
+    }
+}
+
 pub fn get_hostname() -> String {
     let name = system::whoami();
     let host = system::hostname();
@@ -238,11 +403,15 @@ pub fn get_confirmation_content<'a>(block: &Block<'a>, app: &mut App) -> Paragra
 
     let lines_created = files_created
         .iter()
-        .map(|file| {
-            Line::from(vec![
+        .map(|file| match &file.source {
+            Some(source) => Line::from(vec![
+                Span::styled("COPY ", Style::default().fg(Color::Yellow)),
+                Span::raw(format!("{} -> {}", source, file.path())),
+            ]),
+            None => Line::from(vec![
                 Span::styled("CREATE ", Style::default().fg(Color::Cyan)),
                 Span::raw(file.name.clone()),
-            ])
+            ]),
         })
         .collect::<Vec<_>>();
 
@@ -261,7 +430,7 @@ pub fn get_confirmation_content<'a>(block: &Block<'a>, app: &mut App) -> Paragra
         .map(|file| {
             Line::from(vec![
                 Span::styled("MOVE ", Style::default().fg(Color::Magenta)),
-                Span::raw(file.name.clone()),
+                Span::raw(format!("{} -> {}", file.original_path(), file.path())),
             ])
         })
         .collect::<Vec<_>>();
@@ -276,6 +445,101 @@ pub fn get_confirmation_content<'a>(block: &Block<'a>, app: &mut App) -> Paragra
     Paragraph::new(all_lines).block(block.clone())
 }
 
+pub fn get_popup_content<'a>(block: &Block<'a>, popup: &Popup, height: u16) -> Paragraph<'a> {
+    let lines = popup
+        .lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| match popup.selected == Some(index) {
+            true => Line::styled(
+                line.clone(),
+                Style::default().bg(Color::DarkGray).fg(Color::White),
+            ),
+            false => Line::from(line.clone()),
+        })
+        .collect::<Vec<_>>();
+
+    // lists scroll with the selection
+    let scroll = match popup.selected {
+        Some(selected) => follow(selected as u16, height.saturating_sub(2)),
+        None => popup.scroll,
+    };
+
+    Paragraph::new(lines)
+        .block(block.clone())
+        .scroll((scroll, 0))
+}
+
+// the query on top and as many ranked matches as fit below it, scrolled so
+// the selected one stays visible
+pub fn get_finder_content<'a>(block: &Block<'a>, finder: &Finder, height: u16) -> Paragraph<'a> {
+    let visible = (height as usize).saturating_sub(3).max(1);
+    let offset = (finder.selected + 1).saturating_sub(visible);
+
+    let mut lines = vec![Line::from(vec![
+        Span::styled("> ", Style::default().fg(Color::Yellow)),
+        Span::raw(finder.line.content.clone()),
+        // a trailing "+" while more files are still being found
+        Span::styled(
+            match finder.is_walking() {
+                true => format!("  {}+", finder.matches.len()),
+                false => format!("  {}", finder.matches.len()),
+            },
+            Style::default().fg(Color::DarkGray),
+        ),
+    ])];
+
+    for (index, found) in finder.matches.iter().enumerate().skip(offset).take(visible) {
+        let style = match index == finder.selected {
+            true => Style::default().bg(Color::DarkGray).fg(Color::White),
+            false => Style::default(),
+        };
+
+        let spans = found
+            .path
+            .chars()
+            .enumerate()
+            .map(|(position, c)| match found.positions.contains(&position) {
+                true => Span::styled(c.to_string(), style.fg(Color::Yellow)),
+                false => Span::styled(c.to_string(), style),
+            })
+            .collect::<Vec<_>>();
+
+        lines.push(Line::from(spans));
+    }
+
+    Paragraph::new(lines).block(block.clone())
+}
+
+// splits a line into spans so the given char ranges stand out, the last span
+// pads the line so the background covers the whole row
+fn highlight_matches<'a>(line: &str, matches: &[(usize, usize)], style: Style) -> Line<'a> {
+    let chars: Vec<char> = line.chars().collect();
+    let match_style = Style::default().bg(Color::Yellow).fg(Color::Black);
+
+    let mut spans = Vec::new();
+    let mut position = 0;
+    for (start, end) in matches {
+        spans.push(Span::styled(
+            chars[position..*start].iter().collect::<String>(),
+            style,
+        ));
+        spans.push(Span::styled(
+            chars[*start..*end].iter().collect::<String>(),
+            match_style,
+        ));
+        position = *end;
+    }
+
+    let rest: String = chars[position..].iter().collect();
+    spans.push(Span::styled(
+        format!("{:<width$}", rest, width = u16::MAX as usize),
+        style,
+    ));
+
+    Line::from(spans)
+}
+
 pub fn get_line_colors(
     app: &App,
     current_view: &str,
@@ -318,6 +582,13 @@ pub fn get_file_preview_content<'a>(
         }
     };
 
+    // the entry the cursor was on when the directory was last open
+    let remembered = app
+        .positions
+        .get(&child_view.dir)
+        .and_then(|position| position.hovered.clone())
+        .unwrap_or_default();
+
     let mut files = match file_type {
         FileType::File => {
             app.child_preview = system::get_file_preview(hovered_file.clone(), 50)
@@ -331,7 +602,7 @@ pub fn get_file_preview_content<'a>(
             .unwrap_or("Error Reading".to_string())
             .lines()
             .map(|line| {
-                let (bg, fg) = get_line_colors(app, &child_view.dir, line, &hovered_file);
+                let (bg, fg) = get_line_colors(app, &child_view.dir, line, &remembered);
 
                 Line::from(vec![Span::styled(
                     line.to_string(),
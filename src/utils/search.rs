@@ -0,0 +1,130 @@
+use crate::utils::command_line::CommandLine;
+use regex::{Regex, RegexBuilder};
+
+// `/` and `?` over the current listing. The pattern stays highlighted until
+// `:nohlsearch` or the next search
+#[derive(Debug, Clone)]
+pub struct Search {
+    pub line: CommandLine,
+    pub pattern: Option<String>,
+    pub forward: bool,
+    pub highlight: bool,
+    // where the cursor was when typing started, incremental search moves
+    // from here and Esc goes back to it
+    pub origin: (u16, u16),
+}
+
+impl Default for Search {
+    fn default() -> Self {
+        Self {
+            line: CommandLine::new("search_history"),
+            pattern: None,
+            forward: true,
+            highlight: false,
+            origin: (1, 1),
+        }
+    }
+}
+
+impl Search {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn prompt(&self) -> char {
+        match self.forward {
+            true => '/',
+            false => '?',
+        }
+    }
+}
+
+// with smartcase an uppercase letter makes the pattern case sensitive again
+pub fn compile(pattern: &str, ignore_case: bool, smart_case: bool) -> Result<Regex, String> {
+    if pattern.is_empty() {
+        return Err(String::from("E35: No previous regular expression"));
+    }
+
+    let has_upper = pattern.chars().any(|c| c.is_uppercase());
+
+    RegexBuilder::new(pattern)
+        .case_insensitive(ignore_case && !(smart_case && has_upper))
+        .build()
+        .map_err(|_| format!("E383: Invalid search string: {}", pattern))
+}
+
+// char ranges [start, end) of every match on a line
+pub fn matches(line: &str, regex: &Regex) -> Vec<(usize, usize)> {
+    regex
+        .find_iter(line)
+        .filter(|found| !found.is_empty())
+        .map(|found| {
+            (
+                line[..found.start()].chars().count(),
+                line[..found.end()].chars().count(),
+            )
+        })
+        .collect()
+}
+
+// the next match strictly after (or before) the 0-based (row, col) position,
+// wrapping around the listing. The flag tells whether it wrapped
+pub fn find(
+    content: &str,
+    regex: &Regex,
+    (row, col): (usize, usize),
+    forward: bool,
+) -> Option<((usize, usize), bool)> {
+    let lines: Vec<Vec<(usize, usize)>> =
+        content.lines().map(|line| matches(line, regex)).collect();
+
+    let positions = lines
+        .iter()
+        .enumerate()
+        .flat_map(|(index, found)| found.iter().map(move |(start, _)| (index, *start)));
+
+    match forward {
+        true => {
+            let mut positions = positions.peekable();
+            let first = *positions.peek()?;
+
+            match positions.find(|position| *position > (row, col)) {
+                Some(position) => Some((position, false)),
+                None => Some((first, true)),
+            }
+        }
+        false => {
+            let positions: Vec<(usize, usize)> = positions.collect();
+            let last = *positions.last()?;
+
+            match positions
+                .into_iter()
+                .rev()
+                .find(|position| *position < (row, col))
+            {
+                Some(position) => Some((position, false)),
+                None => Some((last, true)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_patterns_are_reported() {
+        assert_eq!(
+            compile("[", false, false).unwrap_err(),
+            "E383: Invalid search string: ["
+        );
+    }
+
+    #[test]
+    fn smartcase_keeps_uppercase_patterns_case_sensitive() {
+        assert!(compile("readme", true, true).unwrap().is_match("README"));
+        assert!(!compile("Readme", true, true).unwrap().is_match("README"));
+        assert!(compile("Readme", true, false).unwrap().is_match("README"));
+    }
+}
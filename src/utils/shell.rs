@@ -0,0 +1,256 @@
+use crate::app::App;
+use crate::file_helper::PathHelper;
+use crate::utils::{
+    command_registry::CommandRegistry,
+    commands,
+    ex_command::{ExCommand, Range},
+    popup::Popup,
+    system,
+};
+use std::path::Path;
+
+pub fn register(registry: &mut CommandRegistry) {
+    registry.register("!", shell);
+    registry.register("r[ead]", read);
+}
+
+// names are passed to `sh`, anything beyond the plain characters is quoted
+fn quote(name: &str) -> String {
+    let plain = |c: char| c.is_alphanumeric() || "-_./+,:@%=".contains(c);
+
+    match !name.is_empty() && name.chars().all(plain) {
+        true => name.to_string(),
+        false => format!("'{}'", name.replace('\'', "'\\''")),
+    }
+}
+
+// vim's filename modifiers: `:p` full path, `:h` head, `:t` tail, `:r` root
+// and `:e` extension, they can be chained like `%:p:h`
+fn apply_modifiers(dir: &str, name: &str, modifiers: &str) -> String {
+    let mut path = name.to_string();
+
+    for modifier in modifiers.split(':').filter(|modifier| !modifier.is_empty()) {
+        path = match modifier {
+            "p" => format!("{}/{}", dir.trim_end_matches('/'), path),
+            "h" => match Path::new(&path).parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => {
+                    parent.to_string_lossy().to_string()
+                }
+                _ => String::from("."),
+            },
+            "t" => Path::new(&path)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or(path),
+            "r" => match path.rfind('.') {
+                Some(index) if index > 0 && !path[..index].ends_with('/') => {
+                    path[..index].to_string()
+                }
+                _ => path,
+            },
+            "e" => Path::new(&path)
+                .extension()
+                .map(|extension| extension.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            _ => path,
+        };
+    }
+
+    path
+}
+
+// `%` is the hovered entry and `%*` every entry in the command's range (the
+// visual selection with `:'<,'>!`), both take modifiers. `\%` is a plain %
+pub fn expand_placeholders(
+    command: &str,
+    dir: &str,
+    hovered: &str,
+    selected: &[String],
+) -> Result<String, String> {
+    let mut expanded = String::new();
+    let mut rest = command;
+
+    while let Some(index) = rest.find(['%', '\\']) {
+        expanded.push_str(&rest[..index]);
+        rest = &rest[index..];
+
+        if let Some(escaped) = rest.strip_prefix("\\%") {
+            expanded.push('%');
+            rest = escaped;
+            continue;
+        }
+
+        if let Some(backslash) = rest.strip_prefix('\\') {
+            expanded.push('\\');
+            rest = backslash;
+            continue;
+        }
+
+        rest = &rest[1..];
+        let (names, after) = match rest.strip_prefix('*') {
+            Some(after) => (selected.to_vec(), after),
+            None => (vec![hovered.to_string()], rest),
+        };
+
+        // modifiers are `:` followed by a single letter
+        let mut modifiers_length = 0;
+        while let [b':', letter, ..] = after.as_bytes()[modifiers_length..] {
+            if !b"phtre".contains(&letter) {
+                break;
+            }
+            modifiers_length += 2;
+        }
+
+        if names.iter().all(|name| name.is_empty() || name == "../") {
+            return Err(String::from("E499: Empty file name for '%'"));
+        }
+
+        let modifiers = &after[..modifiers_length];
+        let expanded_names: Vec<String> = names
+            .iter()
+            .filter(|name| !name.is_empty() && *name != "../")
+            .map(|name| quote(&apply_modifiers(dir, name.trim(), modifiers)))
+            .collect();
+
+        expanded.push_str(&expanded_names.join(" "));
+        rest = &after[modifiers_length..];
+    }
+
+    expanded.push_str(rest);
+
+    Ok(expanded)
+}
+
+// the entry on a 0-based line relative to the directory commands run in, in
+// tree mode a nested one is prefixed with the branch it is in
+fn entry_name(app: &App, y: usize) -> String {
+    let line = app.buffer_content.lines().nth(y).unwrap_or_default().trim();
+    let current = app.path.get_absolute_path();
+    let dir = app.line_dir(y);
+
+    match dir
+        .trim_end_matches('/')
+        .strip_prefix(current.trim_end_matches('/'))
+    {
+        Some(branch) if branch.starts_with('/') && !line.is_empty() => {
+            format!("{}/{}", &branch[1..], line)
+        }
+        _ => line.to_string(),
+    }
+}
+
+fn expand(app: &App, command: &ExCommand, line: &str) -> Result<String, String> {
+    let (start, end) = commands::line_range(app, command, Range::current())?;
+    let selected: Vec<String> = (start - 1..end).map(|y| entry_name(app, y)).collect();
+
+    let hovered = entry_name(app, app.cursor.y.max(1) as usize - 1);
+
+    expand_placeholders(line, &app.path.get_absolute_path(), &hovered, &selected)
+}
+
+fn run(app: &App, line: &str) -> Result<(String, Option<i32>), String> {
+    system::run_shell(line, &app.path.get_absolute_path())
+        .map_err(|error| format!("E282: Cannot execute shell: {}", error))
+}
+
+// what is on disk in every directory that has a view, to tell whether a
+// command changed any of them
+fn listed_on_disk(app: &App) -> Vec<(String, Vec<String>)> {
+    let mut dirs: Vec<&String> = app.buffer_storage.views.keys().collect();
+    dirs.sort();
+
+    dirs.into_iter()
+        .map(|dir| {
+            let mut names: Vec<String> = std::fs::read_dir(dir)
+                .map(|entries| {
+                    entries
+                        .filter_map(Result::ok)
+                        .map(|entry| entry.file_name().to_string_lossy().to_string())
+                        .collect()
+                })
+                .unwrap_or_default();
+            names.sort();
+
+            (dir.clone(), names)
+        })
+        .collect()
+}
+
+fn shell(app: &mut App, command: &ExCommand) -> Result<(), String> {
+    if command.args.trim().is_empty() {
+        return Err(String::from("E471: Argument required"));
+    }
+
+    let line = expand(app, command, &command.args)?;
+    let listed = listed_on_disk(app);
+    let (output, status) = run(app, &line)?;
+
+    let mut lines: Vec<String> = output.lines().map(String::from).collect();
+    match status {
+        Some(0) => {}
+        Some(code) => lines.push(format!("shell returned {}", code)),
+        None => lines.push(String::from("shell was terminated")),
+    }
+
+    // files may have been changed behind our back, reading them again
+    // starts a new undo history so it only happens when they were
+    if !app.buffer_storage.has_changes() && listed_on_disk(app) != listed {
+        app.reload();
+    }
+
+    match lines.is_empty() {
+        true => app.echo(&format!(":!{}", line)),
+        false => app.popup = Some(Popup::new(&format!("!{}", line), lines)),
+    }
+
+    Ok(())
+}
+
+// `:r !cmd` and `:r file` add every line as a new entry below the cursor
+fn read(app: &mut App, command: &ExCommand) -> Result<(), String> {
+    // `:r!cmd` parses as a bang
+    let shell_line = match command.bang {
+        true => Some(command.args.trim()),
+        false => command.args.trim().strip_prefix('!'),
+    };
+
+    let content = match shell_line {
+        Some(line) => {
+            let line = expand(app, command, line)?;
+            let (output, status) = run(app, &line)?;
+
+            if status != Some(0) {
+                return Err(format!("E485: Shell returned {}", status.unwrap_or(-1)));
+            }
+
+            output
+        }
+        None if command.args.trim().is_empty() => {
+            return Err(String::from("E32: No file name"));
+        }
+        None => {
+            let path = PathHelper::expand(command.args.trim(), &app.path.get_absolute_path());
+
+            std::fs::read_to_string(&path)
+                .map_err(|_| format!("E484: Can't open file {}", command.args.trim()))?
+        }
+    };
+
+    let (_, end) = commands::line_range(app, command, Range::current())?;
+    let mut y = end;
+
+    for name in content.lines().map(str::trim) {
+        let exists = app.buffer_content.lines().any(|line| line.trim() == name);
+        if name.is_empty() || exists {
+            continue;
+        }
+
+        app.insert_line(y as u16, name);
+        y += 1;
+    }
+
+    app.cursor.y = y as u16;
+    app.cursor.reset_x();
+
+    Ok(())
+}
@@ -0,0 +1,205 @@
+use crate::app::App;
+use crate::utils::{
+    command_registry::CommandRegistry,
+    commands,
+    ex_command::{ExCommand, Range},
+};
+use regex::{Regex, RegexBuilder};
+
+// `:s/pattern/replacement/flags`. The pattern uses the regex crate's syntax,
+// the replacement takes vim's `\1` and `&` as well as `$1` and `${name}`
+#[derive(Debug, Clone)]
+pub struct Substitution {
+    pub pattern: Regex,
+    pub replacement: String,
+    pub global: bool,
+}
+
+pub fn register(registry: &mut CommandRegistry) {
+    registry.register("s[ubstitute]", substitute);
+}
+
+// splits at the unescaped delimiter, an escaped delimiter loses its backslash
+pub fn split_delimited(input: &str, delimiter: char) -> (String, Option<&str>) {
+    let mut part = String::new();
+    let mut chars = input.char_indices();
+
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some((_, next)) if next == delimiter => part.push(next),
+                Some((_, next)) => {
+                    part.push('\\');
+                    part.push(next);
+                }
+                None => part.push('\\'),
+            },
+            c if c == delimiter => return (part, Some(&input[index + c.len_utf8()..])),
+            c => part.push(c),
+        }
+    }
+
+    (part, None)
+}
+
+// turns vim's replacement syntax into the one `Regex::replace` expects
+fn convert_replacement(replacement: &str) -> String {
+    let mut converted = String::new();
+    let mut chars = replacement.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '&' => converted.push_str("${0}"),
+            '\\' => match chars.next() {
+                Some(digit) if digit.is_ascii_digit() => {
+                    converted.push_str(&format!("${{{}}}", digit))
+                }
+                Some('$') => converted.push_str("$$"),
+                Some('t') => converted.push('\t'),
+                Some(next) => converted.push(next),
+                None => converted.push('\\'),
+            },
+            c => converted.push(c),
+        }
+    }
+
+    converted
+}
+
+impl Substitution {
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let delimiter = match input.chars().next() {
+            Some(c) if !c.is_alphanumeric() && !c.is_whitespace() && c != '\\' => c,
+            Some(_) => {
+                return Err(String::from(
+                    "E146: Regular expressions can't be delimited by letters",
+                ))
+            }
+            None => return Err(String::from("E35: No previous regular expression")),
+        };
+
+        let (pattern, rest) = split_delimited(&input[delimiter.len_utf8()..], delimiter);
+        let (replacement, rest) = match rest {
+            Some(rest) => split_delimited(rest, delimiter),
+            None => (String::new(), None),
+        };
+
+        if pattern.is_empty() {
+            return Err(String::from("E35: No previous regular expression"));
+        }
+
+        let mut global = false;
+        let mut ignore_case = false;
+        for flag in rest.unwrap_or("").trim().chars() {
+            match flag {
+                'g' => global = !global,
+                'i' => ignore_case = true,
+                'I' => ignore_case = false,
+                _ => return Err(format!("E488: Trailing characters: {}", flag)),
+            }
+        }
+
+        let pattern = RegexBuilder::new(&pattern)
+            .case_insensitive(ignore_case)
+            .build()
+            .map_err(|_| format!("E486: Invalid pattern: {}", pattern))?;
+
+        Ok(Self {
+            pattern,
+            replacement: convert_replacement(&replacement),
+            global,
+        })
+    }
+
+    // the substituted line and how many matches were replaced
+    pub fn apply(&self, line: &str) -> Option<(String, usize)> {
+        // replacen treats a limit of 0 as "replace all"
+        let limit = if self.global { 0 } else { 1 };
+        let count = match self.global {
+            true => self.pattern.find_iter(line).count(),
+            false => self.pattern.find_iter(line).take(1).count(),
+        };
+
+        if count == 0 {
+            return None;
+        }
+
+        let replaced = self
+            .pattern
+            .replacen(line, limit, self.replacement.as_str())
+            .to_string();
+
+        Some((replaced, count))
+    }
+}
+
+// lines 1-based, "../" on the first line is never touched
+fn substitute_lines(
+    lines: &[&str],
+    substitution: &Substitution,
+    (start, end): (usize, usize),
+) -> Vec<(usize, String, usize)> {
+    (start.max(2)..=end)
+        .filter_map(|line_number| {
+            let line = lines.get(line_number - 1)?;
+            let (replaced, count) = substitution.apply(line)?;
+
+            Some((line_number, replaced, count))
+        })
+        .collect()
+}
+
+fn substitute(app: &mut App, command: &ExCommand) -> Result<(), String> {
+    let substitution = Substitution::parse(&command.args)?;
+    let range = commands::line_range(app, command, Range::current())?;
+
+    let lines: Vec<&str> = app.buffer_content.lines().collect();
+    let changes = substitute_lines(&lines, &substitution, range);
+
+    if changes.is_empty() {
+        return Err(format!(
+            "E486: Pattern not found: {}",
+            substitution.pattern.as_str()
+        ));
+    }
+
+    let count: usize = changes.iter().map(|(_, _, count)| count).sum();
+    let line_count = changes.len();
+    let last_line = changes.last().map(|(line, _, _)| *line).unwrap_or(1);
+
+    // every changed line turns into a pending rename, :w shows them all
+    for (line_number, replaced, _) in changes {
+        app.replace_line(line_number as u16 - 1, &replaced);
+    }
+
+    app.cursor.y = last_line as u16;
+    app.cursor.reset_x();
+
+    if line_count > 1 {
+        app.echo(&format!("{} substitutions on {} lines", count, line_count));
+    }
+
+    Ok(())
+}
+
+// the listing as it would look after the substitution that is being typed,
+// None whenever the command line doesn't hold a complete substitute yet
+pub fn preview(app: &App, line: &str) -> Option<Vec<(usize, String)>> {
+    let command = ExCommand::parse(line).ok()?;
+    let spec = app.commands.find(&command.name)?;
+
+    if spec.name != "substitute" {
+        return None;
+    }
+
+    let substitution = Substitution::parse(&command.args).ok()?;
+    let range = commands::line_range(app, &command, Range::current()).ok()?;
+    let lines: Vec<&str> = app.buffer_content.lines().collect();
+
+    Some(
+        substitute_lines(&lines, &substitution, range)
+            .into_iter()
+            .map(|(line_number, replaced, _)| (line_number, replaced))
+            .collect(),
+    )
+}
@@ -1,3 +1,5 @@
+use std::env;
+use std::path::PathBuf;
 use std::process;
 
 pub fn whoami() -> String {
@@ -126,14 +128,7 @@ pub fn get_file_size(filename: String) -> String {
 }
 
 pub fn delete_file(file_name: String) -> Result<String, std::io::Error> {
-    let output = std::process::Command::new("rm")
-        .arg("-rf")
-        .arg(file_name)
-        .output()?;
-
-    let result = String::from_utf8_lossy(&output.stdout);
-
-    Ok(result.trim().to_string())
+    run(std::process::Command::new("rm").arg("-rf").arg(file_name))
 }
 
 pub fn rename_file(old_name: String, new_name: String) -> Result<String, std::io::Error> {
@@ -154,18 +149,62 @@ pub fn create_file(file_name: String) -> Result<String, std::io::Error> {
         "touch"
     };
 
-    let output = std::process::Command::new(cmd).arg(file_name).output()?;
+    run(std::process::Command::new(cmd).arg(file_name))
+}
 
-    let result = String::from_utf8_lossy(&output.stdout);
+// runs a line through `sh -c` in `dir`, stdout and stderr are returned
+// together with the exit code
+pub fn run_shell(command: &str, dir: &str) -> Result<(String, Option<i32>), std::io::Error> {
+    let output = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .current_dir(dir)
+        .stdin(std::process::Stdio::null())
+        .output()?;
 
-    Ok(result.trim().to_string())
+    let mut result = String::from_utf8_lossy(&output.stdout).to_string();
+    result.push_str(&String::from_utf8_lossy(&output.stderr));
+
+    Ok((result, output.status.code()))
+}
+
+// opens a file in $VISUAL or $EDITOR (vi if neither is set), the editor may
+// come with arguments like `code -w`
+pub fn open_in_editor(path: &str, dir: &str) -> Result<Option<i32>, std::io::Error> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| String::from("vi"));
+
+    let status = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$1\"", editor))
+        .arg("sh")
+        .arg(path)
+        .current_dir(dir)
+        .status()?;
+
+    Ok(status.code())
 }
 
 pub fn move_file(file_name: String, new_dir: String) -> Result<String, std::io::Error> {
-    let output = std::process::Command::new("mv")
-        .arg(file_name)
-        .arg(new_dir)
-        .output()?;
+    run(std::process::Command::new("mv").arg(file_name).arg(new_dir))
+}
+
+pub fn copy_file(source: String, target: String) -> Result<String, std::io::Error> {
+    run(std::process::Command::new("cp")
+        .arg("-r")
+        .arg(source)
+        .arg(target))
+}
+
+// fails with what the command printed when it exits with an error
+fn run(command: &mut std::process::Command) -> Result<String, std::io::Error> {
+    let output = command.output()?;
+
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(std::io::Error::other(error.trim().to_string()));
+    }
 
     let result = String::from_utf8_lossy(&output.stdout);
 
@@ -214,3 +253,32 @@ pub fn get_dir_preview(dir_name: String) -> Result<String, std::io::Error> {
 
     Ok(res.join("\n"))
 }
+
+fn xdg_dir(variable: &str, fallback: &str) -> PathBuf {
+    let base = match env::var(variable) {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var("HOME").unwrap_or_default()).join(fallback),
+    };
+
+    base.join("foil")
+}
+
+// $XDG_DATA_HOME/foil, holds state that survives between runs
+pub fn data_dir() -> PathBuf {
+    xdg_dir("XDG_DATA_HOME", ".local/share")
+}
+
+pub fn config_dir() -> PathBuf {
+    xdg_dir("XDG_CONFIG_HOME", ".config")
+}
+
+pub fn write_data_file(name: &str, content: &str) -> Result<(), std::io::Error> {
+    let dir = data_dir();
+    std::fs::create_dir_all(&dir)?;
+
+    std::fs::write(dir.join(name), content)
+}
+
+pub fn read_data_file(name: &str) -> Option<String> {
+    std::fs::read_to_string(data_dir().join(name)).ok()
+}
@@ -0,0 +1,86 @@
+use crate::utils::{file_helper::PathHelper, jump_list::JumpList, undo_tree::UndoTree};
+
+// What a tab keeps to itself. The pending file operations are shared, so
+// `:w` applies the edits of every tab at once
+#[derive(Debug)]
+pub struct Tab {
+    pub path: PathHelper,
+    pub buffer_content: String,
+    // x, y and scroll
+    pub cursor: (u16, u16, u16),
+    pub undo_tree: UndoTree,
+    pub jump_list: JumpList,
+    pub previous_dir: Option<String>,
+}
+
+// The App works on the active tab directly, its slot here stays empty until
+// another tab is opened and it gets stored away.
+#[derive(Debug)]
+pub struct Tabs {
+    tabs: Vec<Option<Tab>>,
+    pub current: usize,
+}
+
+impl Default for Tabs {
+    fn default() -> Self {
+        Self {
+            tabs: vec![None],
+            current: 0,
+        }
+    }
+}
+
+impl Tabs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn count(&self) -> usize {
+        self.tabs.len()
+    }
+
+    // stores the active tab and hands out the one at `index`
+    pub fn switch(&mut self, active: Tab, index: usize) -> Option<Tab> {
+        let tab = self.tabs.get_mut(index)?.take()?;
+
+        self.tabs[self.current] = Some(active);
+        self.current = index;
+
+        Some(tab)
+    }
+
+    // an empty slot right after the active tab, which is stored away
+    pub fn open(&mut self, active: Tab) {
+        self.tabs[self.current] = Some(active);
+        self.current += 1;
+        self.tabs.insert(self.current, None);
+    }
+
+    // drops the active tab and hands out the one that takes its place
+    pub fn close(&mut self) -> Option<Tab> {
+        if self.tabs.len() < 2 {
+            return None;
+        }
+
+        self.tabs.remove(self.current);
+        self.current = self.current.min(self.tabs.len() - 1);
+
+        self.tabs[self.current].take()
+    }
+
+    // the tabs that are stored away, the active one is not among them
+    pub fn stored_mut(&mut self) -> impl Iterator<Item = &mut Tab> {
+        self.tabs.iter_mut().flatten()
+    }
+
+    // the directory every tab is in, `active` standing in for the current one
+    pub fn paths(&self, active: &str) -> Vec<String> {
+        self.tabs
+            .iter()
+            .map(|tab| match tab {
+                Some(tab) => tab.path.get_absolute_path(),
+                None => active.to_string(),
+            })
+            .collect()
+    }
+}
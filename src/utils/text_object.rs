@@ -0,0 +1,308 @@
+// Text objects operate on a single line (one file name) and return char index
+// ranges [start, end), the same columns the cursor counts
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextObject {
+    Word,
+    BigWord,
+    Stem,
+    Extension,
+    Segment,
+    Pair(char, char),
+    Quote(char),
+}
+
+const SEGMENT_SEPARATORS: [char; 3] = ['-', '_', '.'];
+
+impl TextObject {
+    pub fn from_char(c: char) -> Option<Self> {
+        let object = match c {
+            'w' => Self::Word,
+            'W' => Self::BigWord,
+            'n' => Self::Stem,
+            'e' => Self::Extension,
+            's' => Self::Segment,
+            '(' | ')' | 'b' => Self::Pair('(', ')'),
+            '[' | ']' => Self::Pair('[', ']'),
+            '{' | '}' | 'B' => Self::Pair('{', '}'),
+            '<' | '>' => Self::Pair('<', '>'),
+            '"' | '\'' | '`' => Self::Quote(c),
+            _ => return None,
+        };
+
+        Some(object)
+    }
+
+    pub fn find(&self, line: &str, x: usize, around: bool) -> Option<(usize, usize)> {
+        let chars: Vec<char> = line.chars().collect();
+
+        if chars.is_empty() {
+            return None;
+        }
+
+        let x = x.min(chars.len() - 1);
+
+        match self {
+            Self::Word => find_word(&chars, x, around, false),
+            Self::BigWord => find_word(&chars, x, around, true),
+            Self::Stem => find_stem(&chars, around),
+            Self::Extension => find_extension(&chars, around),
+            Self::Segment => find_segment(&chars, x, around),
+            Self::Pair(open, close) => find_pair(&chars, x, *open, *close, around),
+            Self::Quote(quote) => find_quote(&chars, x, *quote, around),
+        }
+    }
+}
+
+// index of the dot separating stem and extension, dotfiles like `.bashrc`
+// have no extension
+fn extension_dot(chars: &[char]) -> Option<usize> {
+    let dot = chars.iter().rposition(|c| *c == '.')?;
+
+    if chars[..dot].iter().all(|c| *c == '.') || dot == chars.len() - 1 {
+        return None;
+    }
+
+    Some(dot)
+}
+
+fn find_stem(chars: &[char], around: bool) -> Option<(usize, usize)> {
+    match extension_dot(chars) {
+        Some(dot) if around => Some((0, dot + 1)),
+        Some(dot) => Some((0, dot)),
+        None => Some((0, chars.len())),
+    }
+}
+
+fn find_extension(chars: &[char], around: bool) -> Option<(usize, usize)> {
+    let dot = extension_dot(chars)?;
+
+    match around {
+        true => Some((dot, chars.len())),
+        false => Some((dot + 1, chars.len())),
+    }
+}
+
+fn find_segment(chars: &[char], x: usize, around: bool) -> Option<(usize, usize)> {
+    let is_separator = |c: &char| SEGMENT_SEPARATORS.contains(c);
+
+    if is_separator(&chars[x]) {
+        return Some((x, x + 1));
+    }
+
+    let mut start = x;
+    while start > 0 && !is_separator(&chars[start - 1]) {
+        start -= 1;
+    }
+
+    let mut end = x;
+    while end < chars.len() && !is_separator(&chars[end]) {
+        end += 1;
+    }
+
+    if around {
+        // prefer the trailing separator, like `aw` prefers trailing whitespace
+        if end < chars.len() {
+            end += 1;
+        } else {
+            start = start.saturating_sub(1);
+        }
+    }
+
+    Some((start, end))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CharClass {
+    Whitespace,
+    Word,
+    Punctuation,
+}
+
+// WORDs only distinguish whitespace from everything else
+pub fn char_class(c: char, big_word: bool) -> CharClass {
+    if c.is_whitespace() {
+        CharClass::Whitespace
+    } else if big_word || c.is_alphanumeric() || c == '_' {
+        CharClass::Word
+    } else {
+        CharClass::Punctuation
+    }
+}
+
+fn find_word(chars: &[char], x: usize, around: bool, big_word: bool) -> Option<(usize, usize)> {
+    let class = char_class(chars[x], big_word);
+
+    let mut start = x;
+    while start > 0 && char_class(chars[start - 1], big_word) == class {
+        start -= 1;
+    }
+
+    let mut end = x;
+    while end < chars.len() && char_class(chars[end], big_word) == class {
+        end += 1;
+    }
+
+    if around {
+        let trailing = end;
+        while end < chars.len() && chars[end].is_whitespace() {
+            end += 1;
+        }
+
+        if end == trailing {
+            while start > 0 && chars[start - 1].is_whitespace() {
+                start -= 1;
+            }
+        }
+    }
+
+    Some((start, end))
+}
+
+fn find_pair(
+    chars: &[char],
+    x: usize,
+    open: char,
+    close: char,
+    around: bool,
+) -> Option<(usize, usize)> {
+    let start = if chars[x] == open {
+        x
+    } else {
+        let mut depth = 0;
+        let mut index = x;
+        let mut found = None;
+
+        while index > 0 {
+            index -= 1;
+
+            if chars[index] == close {
+                depth += 1;
+            } else if chars[index] == open {
+                if depth == 0 {
+                    found = Some(index);
+                    break;
+                }
+                depth -= 1;
+            }
+        }
+
+        found?
+    };
+
+    let mut depth = 0;
+    let mut end = None;
+    for (index, c) in chars.iter().enumerate().skip(start + 1) {
+        if *c == open {
+            depth += 1;
+        } else if *c == close {
+            if depth == 0 {
+                end = Some(index);
+                break;
+            }
+            depth -= 1;
+        }
+    }
+    let end = end?;
+
+    match around {
+        true => Some((start, end + 1)),
+        false => Some((start + 1, end)),
+    }
+}
+
+fn find_quote(chars: &[char], x: usize, quote: char, around: bool) -> Option<(usize, usize)> {
+    let quotes: Vec<usize> = chars
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| **c == quote)
+        .map(|(index, _)| index)
+        .collect();
+
+    // quotes pair up from the start of the line, pick the pair around the
+    // cursor or the first one after it
+    let mut pairs = quotes.chunks_exact(2).map(|pair| (pair[0], pair[1]));
+    let (start, end) = pairs
+        .clone()
+        .find(|(start, end)| *start <= x && x <= *end)
+        .or_else(|| pairs.find(|(start, _)| *start > x))?;
+
+    match around {
+        true => Some((start, end + 1)),
+        false => Some((start + 1, end)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(line: &str, object: TextObject, x: usize, around: bool) -> Option<String> {
+        let (start, end) = object.find(line, x, around)?;
+        Some(line.chars().skip(start).take(end - start).collect())
+    }
+
+    #[test]
+    fn objects_are_named_like_in_vim() {
+        assert_eq!(TextObject::from_char('n'), Some(TextObject::Stem));
+        assert_eq!(TextObject::from_char('e'), Some(TextObject::Extension));
+        assert_eq!(TextObject::from_char('s'), Some(TextObject::Segment));
+        assert_eq!(TextObject::from_char('b'), Some(TextObject::Pair('(', ')')));
+        assert_eq!(TextObject::from_char(']'), Some(TextObject::Pair('[', ']')));
+        assert_eq!(TextObject::from_char('`'), Some(TextObject::Quote('`')));
+        assert_eq!(TextObject::from_char('x'), None);
+    }
+
+    #[test]
+    fn stem_ends_at_the_last_dot() {
+        assert_eq!(
+            text("archive.tar.gz", TextObject::Stem, 0, false).unwrap(),
+            "archive.tar"
+        );
+        assert_eq!(
+            text("archive.tar.gz", TextObject::Stem, 0, true).unwrap(),
+            "archive.tar."
+        );
+        assert_eq!(
+            text("Makefile", TextObject::Stem, 3, false).unwrap(),
+            "Makefile"
+        );
+    }
+
+    #[test]
+    fn dotfiles_have_no_extension() {
+        assert_eq!(text(".bashrc", TextObject::Extension, 0, false), None);
+        assert_eq!(
+            text(".bashrc", TextObject::Stem, 0, false).unwrap(),
+            ".bashrc"
+        );
+        assert_eq!(text("notes.", TextObject::Extension, 0, false), None);
+    }
+
+    #[test]
+    fn extension_starts_after_the_dot() {
+        assert_eq!(
+            text("main.rs", TextObject::Extension, 0, false).unwrap(),
+            "rs"
+        );
+        assert_eq!(
+            text("main.rs", TextObject::Extension, 0, true).unwrap(),
+            ".rs"
+        );
+    }
+
+    #[test]
+    fn segments_are_split_by_separators() {
+        let line = "my-long_file.txt";
+        assert_eq!(text(line, TextObject::Segment, 4, false).unwrap(), "long");
+        assert_eq!(text(line, TextObject::Segment, 4, true).unwrap(), "long_");
+        assert_eq!(text(line, TextObject::Segment, 14, true).unwrap(), ".txt");
+        assert_eq!(text(line, TextObject::Segment, 2, false).unwrap(), "-");
+    }
+
+    #[test]
+    fn ranges_count_chars() {
+        assert_eq!(TextObject::Extension.find("éa.txt", 0, false), Some((3, 6)));
+        assert_eq!(text("éa.txt", TextObject::Stem, 1, false).unwrap(), "éa");
+        assert_eq!(text("ü-ö.md", TextObject::Segment, 2, false).unwrap(), "ö");
+    }
+}
@@ -0,0 +1,207 @@
+use crate::app::App;
+use std::path::Path;
+
+// In tree mode directories open in place, their entries indented one level
+// below them. A line belongs to the directory it is indented under, so the
+// edits in a branch end up in the view of that directory
+pub const INDENT: &str = "  ";
+
+pub fn indent(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+// the 0-based line a nested entry is indented under, "../" never is one
+pub fn parent_line<S: AsRef<str>>(lines: &[S], y: usize) -> Option<usize> {
+    let depth = indent(lines.get(y)?.as_ref());
+
+    (1..y).rev().find(|index| {
+        let line = lines[*index].as_ref();
+        !line.trim().is_empty() && indent(line) < depth
+    })
+}
+
+// the index right after the last line nested under line y
+pub fn branch_end<S: AsRef<str>>(lines: &[S], y: usize) -> usize {
+    let depth = match lines.get(y) {
+        Some(line) => indent(line.as_ref()),
+        None => return y,
+    };
+
+    // blank lines inside the branch belong to it, the ones after it don't
+    let mut end = y + 1;
+    for (index, line) in lines.iter().enumerate().skip(y + 1) {
+        let line = line.as_ref();
+        if line.trim().is_empty() {
+            continue;
+        }
+        if indent(line) <= depth {
+            break;
+        }
+
+        end = index + 1;
+    }
+
+    end
+}
+
+// a line inserted at `index` goes into the open directory right above it,
+// or next to the line above otherwise
+pub fn indentation_at<S: AsRef<str>>(lines: &[S], index: usize) -> String {
+    let previous = index
+        .checked_sub(1)
+        .and_then(|index| lines.get(index))
+        .map(|line| indent(line.as_ref()))
+        .unwrap_or(0);
+    let next = lines
+        .get(index)
+        .map(|line| indent(line.as_ref()))
+        .unwrap_or(0);
+
+    " ".repeat(previous.max(next))
+}
+
+fn lines(app: &App) -> Vec<String> {
+    app.buffer_content.lines().map(String::from).collect()
+}
+
+// zo, lists the hovered directory under it
+pub fn expand(app: &mut App) -> Result<(), String> {
+    let mut lines = lines(app);
+    let y = app.cursor.y.max(1) as usize - 1;
+
+    if y == 0 || y >= lines.len() || branch_end(&lines, y) > y + 1 {
+        return Ok(());
+    }
+
+    let dir = app.entry_path(y);
+    if !Path::new(&dir).is_dir() {
+        return Err(String::from("E490: No fold found"));
+    }
+
+    app.buffer_storage
+        .add_view(dir.clone())
+        .map_err(|error| error.to_string())?;
+
+    let indentation = " ".repeat(indent(&lines[y])) + INDENT;
+    let children = app
+        .buffer_storage
+        .views
+        .get(&dir)
+        .map(|view| view.listed_names())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|name| format!("{}{}", indentation, name));
+
+    lines.splice(y + 1..y + 1, children);
+    app.buffer_content = lines.join("\n");
+
+    Ok(())
+}
+
+// zc, closes the hovered directory or the one the hovered entry is in. The
+// pending changes made in the branch stay and show up once it is opened
+// again
+pub fn collapse(app: &mut App) -> Result<(), String> {
+    let mut lines = lines(app);
+    let y = app.cursor.y.max(1) as usize - 1;
+
+    let open = match branch_end(&lines, y) > y + 1 {
+        true => y,
+        false => parent_line(&lines, y).ok_or(String::from("E490: No fold found"))?,
+    };
+
+    let end = branch_end(&lines, open);
+    lines.drain(open + 1..end);
+    app.buffer_content = lines.join("\n");
+
+    app.cursor.y = open as u16 + 1;
+    app.cursor.x = app.cursor.x.min(lines[open].len().max(1) as u16);
+
+    Ok(())
+}
+
+// za
+pub fn toggle(app: &mut App) -> Result<(), String> {
+    let lines = lines(app);
+    let y = app.cursor.y.max(1) as usize - 1;
+
+    match branch_end(&lines, y) > y + 1 {
+        true => collapse(app),
+        false => expand(app),
+    }
+}
+
+// leaving tree mode closes every branch, the cursor stays in the one it
+// was in
+pub fn collapse_all(app: &mut App) {
+    let lines = lines(app);
+    let y = app.cursor.y.max(1) as usize - 1;
+
+    let mut top = y;
+    while let Some(parent) = parent_line(&lines, top) {
+        top = parent;
+    }
+
+    let kept = lines.iter().filter(|line| indent(line) == 0).count();
+    let above = lines[..top.min(lines.len())]
+        .iter()
+        .filter(|line| indent(line) == 0)
+        .count();
+
+    app.buffer_content = lines
+        .into_iter()
+        .filter(|line| indent(line) == 0)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    app.cursor.y = (above as u16 + 1).min(kept.max(1) as u16);
+    app.cursor.reset_x();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LINES: [&str; 8] = ["../", "sub", "  deep", "    d", "  a", "", "  b", "top"];
+
+    #[test]
+    fn parent_is_the_closest_line_indented_less() {
+        assert_eq!(parent_line(&LINES, 2), Some(1));
+        assert_eq!(parent_line(&LINES, 3), Some(2));
+        assert_eq!(parent_line(&LINES, 4), Some(1));
+        assert_eq!(parent_line(&LINES, 6), Some(1));
+    }
+
+    #[test]
+    fn top_level_lines_have_no_parent() {
+        assert_eq!(parent_line(&LINES, 1), None);
+        assert_eq!(parent_line(&LINES, 7), None);
+        assert_eq!(parent_line(&LINES, 8), None);
+
+        // "../" is never a parent, even of a line indented below it
+        assert_eq!(parent_line(&["../", "  a"], 1), None);
+    }
+
+    #[test]
+    fn branch_covers_every_nested_line() {
+        assert_eq!(branch_end(&LINES, 1), 7);
+        assert_eq!(branch_end(&LINES, 2), 4);
+        assert_eq!(branch_end(&LINES, 3), 4);
+        assert_eq!(branch_end(&LINES, 7), 8);
+        assert_eq!(branch_end(&LINES, 8), 8);
+    }
+
+    #[test]
+    fn blank_lines_after_a_branch_are_not_part_of_it() {
+        assert_eq!(branch_end(&["../", "sub", "  a", "", "top"], 1), 3);
+    }
+
+    #[test]
+    fn new_lines_go_into_the_open_directory() {
+        assert_eq!(indentation_at(&LINES, 2), "  ");
+        assert_eq!(indentation_at(&LINES, 3), "    ");
+        assert_eq!(indentation_at(&LINES, 7), "  ");
+        assert_eq!(indentation_at(&LINES, 8), "");
+        assert_eq!(indentation_at(&LINES, 0), "");
+    }
+}
@@ -0,0 +1,255 @@
+use crate::utils::buffer_storage::DirBuffer;
+use std::collections::HashMap;
+
+// Everything a change can touch: the text, the pending file operations of
+// the views it changed and where the cursor was
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub buffer_content: String,
+    pub views: HashMap<String, DirBuffer>,
+    pub path: String,
+    pub cursor: (u16, u16),
+}
+
+#[derive(Debug)]
+struct Node {
+    parent: Option<usize>,
+    // the child `redo` follows, the branch we last came back from
+    redo_child: Option<usize>,
+    before: Snapshot,
+    after: Snapshot,
+}
+
+// Branching undo history like vim's. Nodes are stored in creation order so a
+// node's index + 1 is its sequence number, which `g-` and `g+` walk through.
+// `None` as the current node is the original state before any change.
+#[derive(Debug, Default)]
+pub struct UndoTree {
+    nodes: Vec<Node>,
+    current: Option<usize>,
+    root_redo_child: Option<usize>,
+    pending: Option<Snapshot>,
+    // while held, nested key handling doesn't start or close changes
+    held: usize,
+}
+
+impl UndoTree {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // remembers the state a change starts from, whoever takes it back decides
+    // whether a change actually happened. Returns false while held
+    pub fn begin(&mut self, snapshot: Snapshot) -> bool {
+        if self.held > 0 {
+            return false;
+        }
+
+        self.pending = Some(snapshot);
+        true
+    }
+
+    pub fn cancel(&mut self) {
+        self.pending = None;
+    }
+
+    pub fn take_pending(&mut self) -> Option<Snapshot> {
+        match self.held {
+            0 => self.pending.take(),
+            _ => None,
+        }
+    }
+
+    // keeps everything until `release` in the change that is already open,
+    // so replayed keys undo in one step
+    pub fn hold(&mut self) {
+        self.held += 1;
+    }
+
+    pub fn release(&mut self) {
+        self.held = self.held.saturating_sub(1);
+    }
+
+    pub fn push(&mut self, before: Snapshot, after: Snapshot) {
+        let index = self.nodes.len();
+
+        self.nodes.push(Node {
+            parent: self.current,
+            redo_child: None,
+            before,
+            after,
+        });
+
+        match self.current {
+            Some(parent) => self.nodes[parent].redo_child = Some(index),
+            None => self.root_redo_child = Some(index),
+        }
+
+        self.current = Some(index);
+    }
+
+    pub fn undo(&mut self) -> Option<Snapshot> {
+        let index = self.current?;
+        let parent = self.nodes[index].parent;
+
+        match parent {
+            Some(parent) => self.nodes[parent].redo_child = Some(index),
+            None => self.root_redo_child = Some(index),
+        }
+        self.current = parent;
+
+        Some(self.nodes[index].before.clone())
+    }
+
+    pub fn redo(&mut self) -> Option<Snapshot> {
+        let child = match self.current {
+            Some(index) => self.nodes[index].redo_child?,
+            None => self.root_redo_child?,
+        };
+
+        self.current = Some(child);
+
+        Some(self.nodes[child].after.clone())
+    }
+
+    fn sequence(&self) -> usize {
+        self.current.map(|index| index + 1).unwrap_or(0)
+    }
+
+    // the node and everything it was built on, the node itself first
+    fn lineage(&self, index: Option<usize>) -> Vec<usize> {
+        let mut lineage = Vec::new();
+        let mut index = index;
+
+        while let Some(current) = index {
+            lineage.push(current);
+            index = self.nodes[current].parent;
+        }
+
+        lineage
+    }
+
+    // snapshots only hold what their change touched, so getting to another
+    // branch means undoing back to where both meet and redoing from there
+    fn jump_to(&mut self, sequence: usize) -> Vec<Snapshot> {
+        let target = match sequence {
+            0 => None,
+            sequence if sequence <= self.nodes.len() => Some(sequence - 1),
+            _ => return vec![],
+        };
+
+        let lineage = self.lineage(target);
+        let mut steps = Vec::new();
+
+        while let Some(index) = self.current {
+            if lineage.contains(&index) {
+                break;
+            }
+
+            steps.extend(self.undo());
+        }
+
+        let shared = lineage
+            .iter()
+            .position(|index| Some(*index) == self.current)
+            .unwrap_or(lineage.len());
+
+        for index in lineage[..shared].iter().rev() {
+            match self.nodes[*index].parent {
+                Some(parent) => self.nodes[parent].redo_child = Some(*index),
+                None => self.root_redo_child = Some(*index),
+            }
+
+            self.current = Some(*index);
+            steps.push(self.nodes[*index].after.clone());
+        }
+
+        steps
+    }
+
+    // g- and g+ move through states in the order they were created, jumping
+    // between branches when needed
+    pub fn earlier(&mut self) -> Vec<Snapshot> {
+        match self.sequence() {
+            0 => vec![],
+            sequence => self.jump_to(sequence - 1),
+        }
+    }
+
+    pub fn later(&mut self) -> Vec<Snapshot> {
+        self.jump_to(self.sequence() + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(content: &str) -> Snapshot {
+        Snapshot {
+            buffer_content: content.to_string(),
+            views: HashMap::new(),
+            path: String::from("/"),
+            cursor: (1, 1),
+        }
+    }
+
+    fn contents(snapshots: Vec<Snapshot>) -> Vec<String> {
+        snapshots
+            .into_iter()
+            .map(|snapshot| snapshot.buffer_content)
+            .collect()
+    }
+
+    // a -> b -> c, then undo to b and branch off to d
+    fn branched() -> UndoTree {
+        let mut tree = UndoTree::new();
+        tree.push(state("a"), state("b"));
+        tree.push(state("b"), state("c"));
+        tree.undo();
+        tree.push(state("b"), state("d"));
+
+        tree
+    }
+
+    #[test]
+    fn undo_and_redo_follow_the_last_branch() {
+        let mut tree = branched();
+
+        assert_eq!(tree.undo().map(|s| s.buffer_content).as_deref(), Some("b"));
+        assert_eq!(tree.undo().map(|s| s.buffer_content).as_deref(), Some("a"));
+        assert!(tree.undo().is_none());
+        assert_eq!(tree.redo().map(|s| s.buffer_content).as_deref(), Some("b"));
+        assert_eq!(tree.redo().map(|s| s.buffer_content).as_deref(), Some("d"));
+        assert!(tree.redo().is_none());
+    }
+
+    #[test]
+    fn earlier_walks_over_to_the_other_branch() {
+        let mut tree = branched();
+
+        // from d (3) to c (2): back to b, then forward to c
+        assert_eq!(contents(tree.earlier()), vec!["b", "c"]);
+        assert_eq!(contents(tree.earlier()), vec!["b"]);
+        assert_eq!(contents(tree.earlier()), vec!["a"]);
+        assert!(tree.earlier().is_empty());
+
+        assert_eq!(contents(tree.later()), vec!["b"]);
+        assert_eq!(contents(tree.later()), vec!["c"]);
+        assert_eq!(contents(tree.later()), vec!["b", "d"]);
+        assert!(tree.later().is_empty());
+    }
+
+    #[test]
+    fn held_changes_are_not_started() {
+        let mut tree = UndoTree::new();
+
+        tree.hold();
+        assert!(!tree.begin(state("a")));
+        assert!(tree.take_pending().is_none());
+
+        tree.release();
+        assert!(tree.begin(state("a")));
+        assert!(tree.take_pending().is_some());
+    }
+}
@@ -0,0 +1,203 @@
+use crate::utils::text_object::{char_class, CharClass};
+
+// Word motions work on the whole buffer flattened into one char vector with
+// '\n' between lines, positions are converted back to (row, col) at the end.
+pub struct WordMotion {
+    chars: Vec<char>,
+    big_word: bool,
+}
+
+impl WordMotion {
+    pub fn new(content: &str, big_word: bool) -> Self {
+        Self {
+            chars: content.chars().collect(),
+            big_word,
+        }
+    }
+
+    fn class(&self, index: usize) -> CharClass {
+        char_class(self.chars[index], self.big_word)
+    }
+
+    // an empty line is a word of its own for `w` and `b`
+    fn is_empty_line(&self, index: usize) -> bool {
+        self.chars[index] == '\n' && (index == 0 || self.chars[index - 1] == '\n')
+    }
+
+    pub fn to_index(&self, row: usize, col: usize) -> usize {
+        let mut index = 0;
+
+        for _ in 0..row {
+            match self.chars[index..].iter().position(|c| *c == '\n') {
+                Some(newline) => index += newline + 1,
+                None => break,
+            }
+        }
+
+        let line_length = self.chars[index..]
+            .iter()
+            .position(|c| *c == '\n')
+            .unwrap_or(self.chars.len() - index);
+
+        (index + col.min(line_length)).min(self.chars.len().saturating_sub(1))
+    }
+
+    fn line_start(&self, index: usize) -> usize {
+        self.chars[..index]
+            .iter()
+            .rposition(|c| *c == '\n')
+            .map(|newline| newline + 1)
+            .unwrap_or(0)
+    }
+
+    pub fn to_position(&self, index: usize) -> (usize, usize) {
+        let row = self.chars[..index].iter().filter(|c| **c == '\n').count();
+
+        (row, index - self.line_start(index))
+    }
+
+    // returns the buffer length when there is no next word so operators can
+    // tell "end of buffer" apart from "last character"
+    pub fn next_start(&self, index: usize) -> usize {
+        let len = self.chars.len();
+        if index >= len {
+            return len;
+        }
+
+        let mut index = index;
+        let class = self.class(index);
+
+        if class != CharClass::Whitespace {
+            while index < len && self.class(index) == class {
+                index += 1;
+            }
+        }
+
+        while index < len && self.class(index) == CharClass::Whitespace {
+            if index + 1 < len && self.chars[index] == '\n' && self.is_empty_line(index + 1) {
+                return index + 1;
+            }
+            index += 1;
+        }
+
+        index
+    }
+
+    pub fn prev_start(&self, index: usize) -> usize {
+        if index == 0 || self.chars.is_empty() {
+            return 0;
+        }
+
+        let mut index = index - 1;
+
+        while index > 0 && self.class(index) == CharClass::Whitespace {
+            if self.is_empty_line(index) {
+                return index;
+            }
+            index -= 1;
+        }
+
+        let class = self.class(index);
+        while index > 0 && self.class(index - 1) == class {
+            index -= 1;
+        }
+
+        index
+    }
+
+    pub fn next_end(&self, index: usize) -> usize {
+        let len = self.chars.len();
+        if len == 0 {
+            return 0;
+        }
+
+        let mut index = index + 1;
+
+        while index < len && self.class(index) == CharClass::Whitespace {
+            index += 1;
+        }
+
+        if index >= len {
+            return len - 1;
+        }
+
+        let class = self.class(index);
+        while index + 1 < len && self.class(index + 1) == class {
+            index += 1;
+        }
+
+        index
+    }
+
+    pub fn apply(&self, motion: char, index: usize) -> usize {
+        match motion {
+            'w' | 'W' => self.next_start(index),
+            'b' | 'B' => self.prev_start(index),
+            'e' | 'E' => self.next_end(index),
+            _ => index,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn motion(content: &str, motion: char, from: (usize, usize)) -> (usize, usize) {
+        let word_motion = WordMotion::new(content, motion.is_uppercase());
+        let index = word_motion.apply(motion, word_motion.to_index(from.0, from.1));
+
+        word_motion.to_position(index)
+    }
+
+    #[test]
+    fn punctuation_is_a_word_of_its_own() {
+        let line = "my-file.txt";
+        assert_eq!(motion(line, 'w', (0, 0)), (0, 2));
+        assert_eq!(motion(line, 'w', (0, 2)), (0, 3));
+        assert_eq!(motion(line, 'w', (0, 3)), (0, 7));
+        assert_eq!(motion(line, 'e', (0, 0)), (0, 1));
+        assert_eq!(motion(line, 'e', (0, 1)), (0, 2));
+        assert_eq!(motion(line, 'b', (0, 8)), (0, 7));
+        assert_eq!(motion(line, 'b', (0, 7)), (0, 3));
+    }
+
+    #[test]
+    fn words_only_split_at_whitespace() {
+        let line = "my-file.txt";
+        assert_eq!(motion(line, 'E', (0, 0)), (0, 10));
+        assert_eq!(motion(line, 'B', (0, 10)), (0, 0));
+        assert_eq!(motion("a.b c", 'W', (0, 0)), (0, 4));
+    }
+
+    #[test]
+    fn dotfiles_start_with_a_punctuation_word() {
+        assert_eq!(motion(".bashrc", 'w', (0, 0)), (0, 1));
+        assert_eq!(motion(".bashrc", 'e', (0, 0)), (0, 6));
+        assert_eq!(motion(".bashrc", 'b', (0, 4)), (0, 1));
+        assert_eq!(motion(".bashrc", 'B', (0, 4)), (0, 0));
+    }
+
+    #[test]
+    fn motions_cross_the_end_of_the_line() {
+        let content = "a.txt\nb.md";
+        assert_eq!(motion(content, 'w', (0, 2)), (1, 0));
+        assert_eq!(motion(content, 'e', (0, 4)), (1, 0));
+        assert_eq!(motion(content, 'b', (1, 0)), (0, 2));
+        assert_eq!(motion(content, 'W', (0, 0)), (1, 0));
+    }
+
+    #[test]
+    fn empty_lines_stop_w_and_b() {
+        let content = "a\n\nb";
+        assert_eq!(motion(content, 'w', (0, 0)), (1, 0));
+        assert_eq!(motion(content, 'w', (1, 0)), (2, 0));
+        assert_eq!(motion(content, 'b', (2, 0)), (1, 0));
+    }
+
+    #[test]
+    fn w_after_the_last_word_is_the_end_of_the_buffer() {
+        assert_eq!(motion("a.txt", 'w', (0, 2)), (0, 5));
+        assert_eq!(motion("a.txt", 'e', (0, 4)), (0, 4));
+    }
+}
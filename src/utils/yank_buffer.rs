@@ -1,38 +1,200 @@
+use crate::utils::clipboard;
+use std::collections::HashMap;
+
 // Important for pasting. Keeping track of the yank type makes pasting easier
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum YankType {
     Line,
     Word,
     Char,
 }
 
-#[derive(Debug)]
-pub struct YankBuffer {
+#[derive(Debug, Clone)]
+pub struct Register {
     pub content: String,
     pub yank_type: YankType,
+    // the directory every yanked or deleted line comes from, pasting them
+    // into another one moves or copies the entries
+    pub dirs: Vec<String>,
+}
+
+const UNNAMED: char = '"';
+const YANK: char = '0';
+const SMALL_DELETE: char = '-';
+const BLACK_HOLE: char = '_';
+const CLIPBOARD: char = '+';
+const SELECTION: char = '*';
+
+// Registers work like vim's: `"` is the unnamed register every yank and
+// delete goes to, `0` holds the last yank, `1`-`9` the last line deletes,
+// `-` small deletes within a line and `a`-`z` are named (`A`-`Z` append).
+// `+` and `*` also go to the terminal clipboard.
+#[derive(Debug)]
+pub struct YankBuffer {
+    registers: HashMap<char, Register>,
+    selected: Option<char>,
+    // the last write to the terminal clipboard that failed, the key handler
+    // shows it
+    clipboard_error: Option<std::io::Error>,
 }
 
 impl YankBuffer {
     pub fn new() -> Self {
         Self {
-            content: String::new(),
-            yank_type: YankType::Line,
+            registers: HashMap::new(),
+            selected: None,
+            clipboard_error: None,
         }
     }
 
+    pub fn is_valid_register(register: char) -> bool {
+        register.is_ascii_alphanumeric()
+            || [UNNAMED, SMALL_DELETE, BLACK_HOLE, CLIPBOARD, SELECTION].contains(&register)
+    }
+
     pub fn clear(&mut self) {
-        self.content.clear();
+        self.registers.clear();
+        self.selected = None;
+    }
+
+    // the next yank, delete or paste uses this register
+    pub fn select(&mut self, register: char) {
+        if Self::is_valid_register(register) {
+            self.selected = Some(register);
+        }
+    }
+
+    pub fn deselect(&mut self) {
+        self.selected = None;
+    }
+
+    pub fn selected(&self) -> Option<char> {
+        self.selected
+    }
+
+    pub fn take_clipboard_error(&mut self) -> Option<std::io::Error> {
+        self.clipboard_error.take()
+    }
+
+    fn write(&mut self, register: char, content: String, yank_type: YankType, dirs: Vec<String>) {
+        if register.is_ascii_uppercase() {
+            let register = register.to_ascii_lowercase();
+
+            if let Some(existing) = self.registers.get_mut(&register) {
+                let linewise = yank_type == YankType::Line || existing.yank_type == YankType::Line;
+                let separator = if linewise { "\n" } else { "" };
+
+                existing.content = format!("{}{}{}", existing.content, separator, content);
+                if yank_type == YankType::Line {
+                    existing.yank_type = YankType::Line;
+                }
+                existing.dirs.extend(dirs);
+
+                let existing = existing.clone();
+                self.registers.insert(UNNAMED, existing);
+                return;
+            }
+
+            return self.write(register, content, yank_type, dirs);
+        }
+
+        // there is only one terminal clipboard, both names share it
+        let register = match register {
+            SELECTION => CLIPBOARD,
+            register => register,
+        };
+
+        if register == CLIPBOARD {
+            if let Err(error) = clipboard::copy(&content) {
+                self.clipboard_error = Some(error);
+            }
+        }
+
+        let value = Register {
+            content,
+            yank_type,
+            dirs,
+        };
+
+        self.registers.insert(register, value.clone());
+        self.registers.insert(UNNAMED, value);
+    }
+
+    pub fn yank(&mut self, content: String, yank_type: YankType) {
+        self.yank_from(content, yank_type, vec![]);
+    }
+
+    // whole lines of a listing remember which directory they were taken from
+    pub fn yank_from(&mut self, content: String, yank_type: YankType, dirs: Vec<String>) {
+        match self.selected.take() {
+            Some(BLACK_HOLE) => {}
+            Some(register) if register != UNNAMED => self.write(register, content, yank_type, dirs),
+            _ => self.write(YANK, content, yank_type, dirs),
+        }
+    }
+
+    pub fn delete(&mut self, content: String, yank_type: YankType) {
+        self.delete_from(content, yank_type, vec![]);
     }
 
-    pub fn set_yank_type(&mut self, yank_type: YankType) {
-        self.yank_type = yank_type;
+    pub fn delete_from(&mut self, content: String, yank_type: YankType, dirs: Vec<String>) {
+        match self.selected.take() {
+            Some(BLACK_HOLE) => {}
+            Some(register) if register != UNNAMED => self.write(register, content, yank_type, dirs),
+            _ if yank_type == YankType::Line => {
+                for register in (1..9).rev() {
+                    let from = char::from_digit(register, 10).unwrap_or('1');
+                    let to = char::from_digit(register + 1, 10).unwrap_or('9');
+
+                    if let Some(value) = self.registers.remove(&from) {
+                        self.registers.insert(to, value);
+                    }
+                }
+
+                self.write('1', content, yank_type, dirs);
+            }
+            _ => self.write(SMALL_DELETE, content, yank_type, dirs),
+        }
     }
 
-    pub fn get_yank_type(&self) -> &YankType {
-        &self.yank_type
+    pub fn get(&mut self) -> Option<Register> {
+        let register = match self.selected.take().unwrap_or(UNNAMED) {
+            SELECTION => CLIPBOARD,
+            register => register.to_ascii_lowercase(),
+        };
+
+        self.registers.get(&register).cloned()
     }
 
-    pub fn set_content(&mut self, content: String) {
-        self.content = content;
+    pub fn describe(&self) -> Vec<String> {
+        let mut registers: Vec<_> = self.registers.iter().collect();
+        registers.sort_by_key(|(register, _)| {
+            let group = match **register {
+                UNNAMED => 0,
+                register if register.is_ascii_digit() => 1,
+                SMALL_DELETE => 2,
+                CLIPBOARD => 3,
+                _ => 4,
+            };
+
+            (group, **register)
+        });
+
+        let mut lines = vec![String::from("Type Name Content")];
+        for (register, value) in registers {
+            let yank_type = match value.yank_type {
+                YankType::Line => "l",
+                YankType::Word | YankType::Char => "c",
+            };
+
+            lines.push(format!(
+                "  {}  \"{}   {}",
+                yank_type,
+                register,
+                value.content.replace('\n', "^J")
+            ));
+        }
+
+        lines
     }
 }